@@ -0,0 +1,132 @@
+// Recorded answers for each day's data files, stored alongside them in `data/dayN/answers.tsv`
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+use crate::solver::Part;
+use crate::utils::input::Error as InputError;
+
+const ANSWERS_FILE_NAME: &str = "answers.tsv";
+
+/// The confirmed answers for one day, keyed by dataset name and part.
+///
+/// Each line of the file holds a dataset name, a part number and the answer, separated by tabs.
+#[derive(Debug, Default, PartialEq)]
+pub struct AnswerStore {
+    answers: BTreeMap<(String, Part), String>,
+}
+
+impl AnswerStore {
+    pub fn path_for_day(data_dir: &Path, day: u8) -> PathBuf {
+        data_dir.join(format!("day{}", day)).join(ANSWERS_FILE_NAME)
+    }
+
+    /// Loads the answers from `path`, or returns an empty store if the file does not exist yet.
+    pub fn load(path: &Path) -> Result<AnswerStore, AppError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                AnswerStore::parse(&contents).map_err(|err| AppError::invalid_file(path, err))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(AnswerStore::default()),
+            Err(err) => Err(AppError::io(
+                format!("could not read {}", path.display()),
+                err,
+            )),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), AppError> {
+        fs::write(path, self.to_file_contents())
+            .map_err(|err| AppError::io(format!("could not write {}", path.display()), err))
+    }
+
+    fn parse(contents: &str) -> Result<AnswerStore, InputError> {
+        let mut answers = BTreeMap::new();
+        for (i, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != 3 {
+                return Err(InputError::ParseError(format!(
+                    "line {} must have a dataset, part and answer separated by tabs, found: {}",
+                    i + 1,
+                    line
+                )));
+            }
+            let part = fields[1]
+                .parse::<u8>()
+                .ok()
+                .and_then(Part::from_number)
+                .ok_or_else(|| {
+                    InputError::ParseError(format!("invalid part on line {}: {}", i + 1, fields[1]))
+                })?;
+            answers.insert((fields[0].to_string(), part), fields[2].to_string());
+        }
+        Ok(AnswerStore { answers })
+    }
+
+    fn to_file_contents(&self) -> String {
+        self.answers
+            .iter()
+            .map(|((dataset, part), answer)| format!("{}\t{}\t{}\n", dataset, part, answer))
+            .collect()
+    }
+
+    pub fn get(&self, dataset: &str, part: Part) -> Option<&str> {
+        self.answers
+            .get(&(dataset.to_string(), part))
+            .map(String::as_str)
+    }
+
+    pub fn set(&mut self, dataset: &str, part: Part, answer: &str) {
+        self.answers
+            .insert((dataset.to_string(), part), answer.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_answers() {
+        let store = AnswerStore::parse(
+            "# Confirmed answers
+small\t1\t357
+large\t2\t172681562473501
+",
+        )
+        .unwrap();
+
+        assert_eq!(store.get("small", Part::One), Some("357"));
+        assert_eq!(store.get("large", Part::Two), Some("172681562473501"));
+        assert_eq!(store.get("small", Part::Two), None);
+    }
+
+    #[test]
+    fn parse_answers_invalid_part() {
+        assert!(AnswerStore::parse("small\t3\t357").is_err());
+    }
+
+    #[test]
+    fn parse_answers_missing_field() {
+        assert!(AnswerStore::parse("small\t357").is_err());
+    }
+
+    #[test]
+    fn set_then_write_round_trips() {
+        let mut store = AnswerStore::default();
+        store.set("small", Part::Two, "43");
+        store.set("large", Part::One, "1491");
+        store.set("small", Part::Two, "44");
+
+        let contents = store.to_file_contents();
+
+        assert_eq!(contents, "large\t1\t1491\nsmall\t2\t44\n");
+        assert_eq!(AnswerStore::parse(&contents).unwrap(), store);
+    }
+}
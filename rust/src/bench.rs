@@ -0,0 +1,193 @@
+// Repeatedly times a solver, keeping parse and solve times separate
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::solver::{DynSolver, Params, Part};
+use crate::utils::input::Error;
+
+/// Summary statistics over a set of timings, in nanoseconds.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Stats {
+    pub min_ns: u64,
+    pub median_ns: u64,
+    pub p95_ns: u64,
+    pub mean_ns: u64,
+}
+
+impl Stats {
+    fn from_timings(timings: &[Duration]) -> Stats {
+        let mut nanos: Vec<u64> = timings.iter().map(|t| t.as_nanos() as u64).collect();
+        nanos.sort_unstable();
+        if nanos.is_empty() {
+            return Stats {
+                min_ns: 0,
+                median_ns: 0,
+                p95_ns: 0,
+                mean_ns: 0,
+            };
+        }
+
+        let n = nanos.len();
+        let median_ns = if n.is_multiple_of(2) {
+            (nanos[n / 2 - 1] + nanos[n / 2]) / 2
+        } else {
+            nanos[n / 2]
+        };
+        // Nearest-rank percentile.
+        let p95_ns = nanos[(n * 95).div_ceil(100) - 1];
+        let mean_ns = (nanos.iter().map(|&t| t as u128).sum::<u128>() / n as u128) as u64;
+        Stats {
+            min_ns: nanos[0],
+            median_ns,
+            p95_ns,
+            mean_ns,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BenchReport {
+    pub day: u8,
+    pub part: u8,
+    pub dataset: String,
+    pub warmup: usize,
+    pub repeats: usize,
+    pub answer: String,
+    pub parse: Stats,
+    pub solve: Stats,
+}
+
+pub struct BenchOptions {
+    /// Untimed runs before measuring, to warm caches and the allocator.
+    pub warmup: usize,
+    /// Timed runs.
+    pub repeats: usize,
+}
+
+/// Parses and solves the day's input repeatedly, timing each parse and solve.
+pub fn bench(
+    day: u8,
+    solver: &dyn DynSolver,
+    part: Part,
+    params: &Params,
+    dataset: &str,
+    input: &str,
+    options: &BenchOptions,
+) -> Result<BenchReport, AppError> {
+    let mut answer = None;
+    let mut parse_timings = Vec::with_capacity(options.repeats);
+    let mut solve_timings = Vec::with_capacity(options.repeats);
+    for i in 0..(options.warmup + options.repeats) {
+        let start = Instant::now();
+        let parsed = solver.parse_input(&solver.normalize_input(input))?;
+        let parse_time = start.elapsed();
+
+        let start = Instant::now();
+        let run_answer = solver.solve_part(part, parsed.as_ref(), params)?;
+        let solve_time = start.elapsed();
+
+        if let Some(previous) = &answer
+            && *previous != run_answer
+        {
+            return Err(AppError::Solve(Error::LogicError(format!(
+                "answer changed between runs: {} then {}",
+                previous, run_answer
+            ))));
+        }
+        answer = Some(run_answer);
+
+        if i >= options.warmup {
+            parse_timings.push(parse_time);
+            solve_timings.push(solve_time);
+        }
+        log::debug!(
+            "Run {}: parse {:?}, solve {:?}",
+            i + 1,
+            parse_time,
+            solve_time
+        );
+    }
+
+    Ok(BenchReport {
+        day,
+        part: part.number(),
+        dataset: dataset.to_string(),
+        warmup: options.warmup,
+        repeats: options.repeats,
+        answer: answer.map(|a| a.to_string()).unwrap_or_default(),
+        parse: Stats::from_timings(&parse_timings),
+        solve: Stats::from_timings(&solve_timings),
+    })
+}
+
+impl BenchReport {
+    pub fn format_summary(&self) -> String {
+        let format_stats = |name: &str, stats: &Stats| {
+            format!(
+                "{:<5}  min {:>10.2?}  median {:>10.2?}  p95 {:>10.2?}  mean {:>10.2?}\n",
+                name,
+                Duration::from_nanos(stats.min_ns),
+                Duration::from_nanos(stats.median_ns),
+                Duration::from_nanos(stats.p95_ns),
+                Duration::from_nanos(stats.mean_ns),
+            )
+        };
+        format!(
+            "Day {} part {} ({}, {} runs after {} warmup): {}\n{}{}",
+            self.day,
+            self.part,
+            self.dataset,
+            self.repeats,
+            self.warmup,
+            self.answer,
+            format_stats("parse", &self.parse),
+            format_stats("solve", &self.solve),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|&v| Duration::from_millis(v)).collect()
+    }
+
+    #[test]
+    fn stats_odd_count() {
+        let stats = Stats::from_timings(&millis(&[5, 1, 3]));
+        assert_eq!(
+            stats,
+            Stats {
+                min_ns: 1_000_000,
+                median_ns: 3_000_000,
+                p95_ns: 5_000_000,
+                mean_ns: 3_000_000,
+            }
+        );
+    }
+
+    #[test]
+    fn stats_even_count() {
+        let stats = Stats::from_timings(&millis(&[4, 1, 2, 3]));
+        assert_eq!(stats.median_ns, 2_500_000);
+        assert_eq!(stats.mean_ns, 2_500_000);
+    }
+
+    #[test]
+    fn stats_p95_nearest_rank() {
+        let timings: Vec<u64> = (1..=100).collect();
+        let stats = Stats::from_timings(&millis(&timings));
+        assert_eq!(stats.p95_ns, 95_000_000);
+        assert_eq!(stats.min_ns, 1_000_000);
+    }
+
+    #[test]
+    fn stats_empty() {
+        assert_eq!(Stats::from_timings(&[]).mean_ns, 0);
+    }
+}
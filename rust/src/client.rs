@@ -0,0 +1,367 @@
+// Talks to the Advent of Code website, caching puzzle inputs under `data/`
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+use crate::error::AppError;
+use crate::runner;
+use crate::solver::Part;
+use crate::submissions::SubmissionOutcome;
+
+const YEAR: u16 = 2025;
+const USER_AGENT: &str = "github.com/MorganR/advent-of-code-2025";
+/// Records when the website was last contacted, so the minimum interval holds across runs.
+const LAST_REQUEST_FILE_NAME: &str = ".last_request";
+/// Downloaded inputs are stored as the `large` dataset.
+pub const INPUT_DATASET: &str = "large";
+
+#[derive(Debug, PartialEq)]
+pub enum FetchOutcome {
+    /// The input was already cached, so nothing was downloaded.
+    Cached(PathBuf),
+    Downloaded(PathBuf),
+}
+
+/// Extracts the text of the page's `<article>`, where the website explains a submission's outcome.
+fn article_text(html: &str) -> String {
+    let article = html
+        .split_once("<article>")
+        .and_then(|(_, rest)| rest.split_once("</article>"))
+        .map_or(html, |(article, _)| article);
+
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in article.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub struct Client {
+    config: Config,
+    data_dir: PathBuf,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub fn new(config: Config, data_dir: &Path) -> Client {
+        let agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .user_agent(USER_AGENT)
+            .timeout_global(Some(Duration::from_secs(30)))
+            .build()
+            .into();
+        Client {
+            config,
+            data_dir: data_dir.to_path_buf(),
+            agent,
+        }
+    }
+
+    fn day_url(&self, day: u8) -> String {
+        format!(
+            "{}/{}/day/{}",
+            self.config.base_url.trim_end_matches('/'),
+            YEAR,
+            day
+        )
+    }
+
+    /// Sleeps until at least the configured minimum interval has passed since the last request.
+    fn wait_for_min_interval(&self) -> Result<(), AppError> {
+        let path = self.data_dir.join(LAST_REQUEST_FILE_NAME);
+        let last_request_millis = match fs::read_to_string(&path) {
+            Ok(contents) => contents.trim().parse::<u64>().ok(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => {
+                return Err(AppError::io(
+                    format!("could not read {}", path.display()),
+                    err,
+                ));
+            }
+        };
+        if let Some(last_request_millis) = last_request_millis {
+            let next_allowed =
+                UNIX_EPOCH + Duration::from_millis(last_request_millis) + self.config.min_interval;
+            if let Ok(remaining) = next_allowed.duration_since(SystemTime::now()) {
+                log::info!("Waiting {:.1?} before contacting the website", remaining);
+                thread::sleep(remaining);
+            }
+        }
+
+        let now_millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        fs::create_dir_all(&self.data_dir)
+            .and_then(|_| fs::write(&path, now_millis.to_string()))
+            .map_err(|err| AppError::io(format!("could not write {}", path.display()), err))
+    }
+
+    /// Downloads the day's puzzle input to `data/dayN/large.txt`, unless it is already there.
+    ///
+    /// The trailing newline is dropped to match the inputs that were pasted in by hand.
+    pub fn fetch_input(&self, day: u8) -> Result<FetchOutcome, AppError> {
+        let path = runner::data_file_path(&self.data_dir, day, INPUT_DATASET);
+        if path.exists() {
+            return Ok(FetchOutcome::Cached(path));
+        }
+        let session = self.config.require_session()?;
+
+        self.wait_for_min_interval()?;
+        let url = format!("{}/input", self.day_url(day));
+        log::info!("Fetching {}", url);
+        let mut response = self
+            .agent
+            .get(&url)
+            .header("Cookie", &format!("session={}", session))
+            .call()
+            .map_err(|err| AppError::Network(format!("fetching {} failed: {}", url, err)))?;
+        let status = response.status();
+        let body = response
+            .body_mut()
+            .read_to_string()
+            .map_err(|err| AppError::Network(format!("reading {} failed: {}", url, err)))?;
+        if !status.is_success() {
+            return Err(AppError::Network(format!(
+                "fetching {} failed with status {}: {}",
+                url,
+                status.as_u16(),
+                body.trim()
+            )));
+        }
+
+        path.parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, body.strip_suffix('\n').unwrap_or(&body)))
+            .map_err(|err| AppError::io(format!("could not write {}", path.display()), err))?;
+        Ok(FetchOutcome::Downloaded(path))
+    }
+
+    /// Submits an answer, returning the outcome and the website's explanation of it.
+    pub fn submit_answer(
+        &self,
+        day: u8,
+        part: Part,
+        answer: &str,
+    ) -> Result<(SubmissionOutcome, String), AppError> {
+        let session = self.config.require_session()?;
+
+        self.wait_for_min_interval()?;
+        let url = format!("{}/answer", self.day_url(day));
+        log::info!("Submitting {} to {}", answer, url);
+        let level = part.to_string();
+        let mut response = self
+            .agent
+            .post(&url)
+            .header("Cookie", &format!("session={}", session))
+            .send_form([("level", level.as_str()), ("answer", answer)])
+            .map_err(|err| AppError::Network(format!("submitting to {} failed: {}", url, err)))?;
+        let status = response.status();
+        let body = response
+            .body_mut()
+            .read_to_string()
+            .map_err(|err| AppError::Network(format!("reading {} failed: {}", url, err)))?;
+        if !status.is_success() {
+            return Err(AppError::Network(format!(
+                "submitting to {} failed with status {}: {}",
+                url,
+                status.as_u16(),
+                body.trim()
+            )));
+        }
+
+        Ok((SubmissionOutcome::from_response(&body), article_text(&body)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    use super::*;
+
+    /// A request received by [`serve`].
+    struct StubRequest {
+        request_line: String,
+        headers: Vec<String>,
+        body: String,
+    }
+
+    /// Serves `responses` in order on a local port, one per connection, and returns its base URL.
+    fn serve(responses: Vec<(u16, &'static str)>) -> (String, mpsc::Receiver<StubRequest>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut headers = Vec::new();
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    headers.push(header.trim().to_string());
+                }
+                let content_length = headers
+                    .iter()
+                    .find_map(|h| {
+                        h.to_lowercase()
+                            .strip_prefix("content-length:")?
+                            .trim()
+                            .parse()
+                            .ok()
+                    })
+                    .unwrap_or(0);
+                let mut request_body = vec![0; content_length];
+                reader.read_exact(&mut request_body).unwrap();
+                sender
+                    .send(StubRequest {
+                        request_line: request_line.trim().to_string(),
+                        headers,
+                        body: String::from_utf8(request_body).unwrap(),
+                    })
+                    .unwrap();
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+        (base_url, receiver)
+    }
+
+    fn temp_data_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc-client-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn test_config(base_url: String) -> Config {
+        Config {
+            session: Some("secret".to_string()),
+            base_url,
+            min_interval: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn fetch_input_downloads_then_uses_cache() {
+        let (base_url, requests) = serve(vec![(200, "1,2\n3,4\n")]);
+        let data_dir = temp_data_dir("fetch");
+        let client = Client::new(test_config(base_url), &data_dir);
+
+        let path = data_dir.join("day9/large.txt");
+        assert_eq!(
+            client.fetch_input(9).unwrap(),
+            FetchOutcome::Downloaded(path.clone())
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1,2\n3,4");
+
+        let request = requests.recv().unwrap();
+        assert_eq!(request.request_line, "GET /2025/day/9/input HTTP/1.1");
+        assert!(
+            request
+                .headers
+                .iter()
+                .any(|h| h.eq_ignore_ascii_case("cookie: session=secret"))
+        );
+        assert!(request.body.is_empty());
+
+        // The stub only answers once, so a second download would fail.
+        assert_eq!(client.fetch_input(9).unwrap(), FetchOutcome::Cached(path));
+
+        fs::remove_dir_all(&data_dir).unwrap();
+    }
+
+    #[test]
+    fn fetch_input_reports_http_errors() {
+        let (base_url, _requests) = serve(vec![(404, "Please don't repeatedly request this")]);
+        let data_dir = temp_data_dir("fetch-error");
+        let client = Client::new(test_config(base_url), &data_dir);
+
+        let err = client.fetch_input(12).unwrap_err();
+
+        assert!(err.to_string().contains("404"), "{}", err);
+        assert!(!data_dir.join("day12/large.txt").exists());
+        fs::remove_dir_all(&data_dir).unwrap();
+    }
+
+    #[test]
+    fn fetch_input_requires_session() {
+        let data_dir = temp_data_dir("fetch-session");
+        let client = Client::new(
+            Config {
+                session: None,
+                ..test_config("http://127.0.0.1:1".to_string())
+            },
+            &data_dir,
+        );
+
+        assert!(client.fetch_input(3).is_err());
+    }
+
+    #[test]
+    fn submit_answer_parses_outcome() {
+        let (base_url, requests) = serve(vec![(
+            200,
+            "<html><main><article><p>That's not the right answer; your answer is too low. \
+             <a href=\"/2025/day/8\">[Return to Day 8]</a></p></article></main></html>",
+        )]);
+        let data_dir = temp_data_dir("submit");
+        let client = Client::new(test_config(base_url), &data_dir);
+
+        let (outcome, message) = client.submit_answer(8, Part::Two, "25272").unwrap();
+
+        assert_eq!(outcome, SubmissionOutcome::TooLow);
+        assert_eq!(
+            message,
+            "That's not the right answer; your answer is too low. [Return to Day 8]"
+        );
+        let request = requests.recv().unwrap();
+        assert_eq!(request.request_line, "POST /2025/day/8/answer HTTP/1.1");
+        assert_eq!(request.body, "level=2&answer=25272");
+        fs::remove_dir_all(&data_dir).unwrap();
+    }
+
+    #[test]
+    fn wait_for_min_interval_sleeps_between_requests() {
+        let data_dir = temp_data_dir("interval");
+        let client = Client::new(
+            Config {
+                min_interval: Duration::from_millis(200),
+                ..test_config("http://127.0.0.1:1".to_string())
+            },
+            &data_dir,
+        );
+
+        client.wait_for_min_interval().unwrap();
+        let start = std::time::Instant::now();
+        client.wait_for_min_interval().unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(150));
+        fs::remove_dir_all(&data_dir).unwrap();
+    }
+}
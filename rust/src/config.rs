@@ -0,0 +1,152 @@
+// Settings for talking to the Advent of Code website
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::error::AppError;
+use crate::utils::input::Error as InputError;
+
+const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+const DEFAULT_MIN_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Website settings, read from a `key = value` config file and overridden by environment variables.
+///
+/// The file is `$AOC_CONFIG` if set, else `~/.config/aoc/config`. It may set `session`, `base_url`
+/// and `min_interval_secs`. `AOC_SESSION` and `AOC_BASE_URL` override the file.
+#[derive(Debug, PartialEq)]
+pub struct Config {
+    /// The session cookie of a logged-in browser.
+    pub session: Option<String>,
+    pub base_url: String,
+    /// The minimum time to leave between requests to the website.
+    pub min_interval: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            session: None,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            min_interval: DEFAULT_MIN_INTERVAL,
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Result<Config, AppError> {
+        let mut config = match Config::file_path() {
+            Some(path) => match fs::read_to_string(&path) {
+                Ok(contents) => {
+                    Config::parse(&contents).map_err(|err| AppError::invalid_file(&path, err))?
+                }
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Config::default(),
+                Err(err) => {
+                    return Err(AppError::io(
+                        format!("could not read {}", path.display()),
+                        err,
+                    ));
+                }
+            },
+            None => Config::default(),
+        };
+        if let Ok(session) = env::var("AOC_SESSION") {
+            config.session = Some(session);
+        }
+        if let Ok(base_url) = env::var("AOC_BASE_URL") {
+            config.base_url = base_url;
+        }
+        Ok(config)
+    }
+
+    fn file_path() -> Option<PathBuf> {
+        if let Some(path) = env::var_os("AOC_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        env::var_os("HOME").map(|home| PathBuf::from(home).join(".config/aoc/config"))
+    }
+
+    fn parse(contents: &str) -> Result<Config, InputError> {
+        let mut config = Config::default();
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(|| {
+                InputError::ParseError(format!("line {} must be key = value: {}", i + 1, line))
+            })?;
+            let value = value.trim();
+            match key.trim() {
+                "session" => config.session = Some(value.to_string()),
+                "base_url" => config.base_url = value.to_string(),
+                "min_interval_secs" => {
+                    let secs = value.parse().map_err(|err| {
+                        InputError::ParseError(format!(
+                            "invalid min_interval_secs {}: {:?}",
+                            value, err
+                        ))
+                    })?;
+                    config.min_interval = Duration::from_secs(secs);
+                }
+                other => {
+                    return Err(InputError::ParseError(format!(
+                        "unknown key {} on line {}",
+                        other,
+                        i + 1
+                    )));
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn require_session(&self) -> Result<&str, AppError> {
+        self.session.as_deref().ok_or_else(|| {
+            AppError::Usage(
+                "no session token; set AOC_SESSION or add `session = ...` to the config file"
+                    .to_string(),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        let config = Config::parse(
+            "# Advent of Code settings
+session = abc123
+base_url = http://127.0.0.1:8080
+min_interval_secs = 2
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                session: Some("abc123".to_string()),
+                base_url: "http://127.0.0.1:8080".to_string(),
+                min_interval: Duration::from_secs(2),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_config_defaults() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn parse_config_invalid() {
+        assert!(Config::parse("session").is_err());
+        assert!(Config::parse("colour = blue").is_err());
+        assert!(Config::parse("min_interval_secs = soon").is_err());
+    }
+}
@@ -1,125 +1,448 @@
+use num_bigint::{BigInt, BigUint};
+
+use crate::solver::{Answer, Explanation, Param, ParamOverride, Params, Part, Solver};
+use crate::utils::input::Error;
+
+/// The largest base, whose batteries are labelled `0`-`9` then `a`-`z`.
+const MAX_BASE: u32 = 36;
+
 #[derive(Debug, PartialEq)]
 struct BatteryBankSelection {
     /// The indices of the batteries to turn on in the bank.
     batteries: Vec<usize>,
     /// The joltage of this bank from turning on these batteries.
-    joltage: u64,
+    joltage: BigUint,
 }
 
 impl BatteryBankSelection {
-    fn new(batteries: Vec<usize>, joltage: u64) -> BatteryBankSelection {
-        BatteryBankSelection { batteries, joltage }
+    fn new(batteries: Vec<usize>, joltage: impl Into<BigUint>) -> BatteryBankSelection {
+        BatteryBankSelection {
+            batteries,
+            joltage: joltage.into(),
+        }
     }
 }
 
-/// Finds the joltage for the battery bank, defined as the max two digits
-fn select_max_joltage(battery_bank: &str, num_batteries: u8) -> BatteryBankSelection {
-    let mut selected_batteries = Vec::with_capacity(num_batteries.into());
-
-    let last_full_index = battery_bank.len() - num_batteries as usize;
-    for (i, c) in battery_bank.char_indices() {
-        let mut is_done = false;
-        for i_selected in 0..selected_batteries.len() {
-            let last_index = last_full_index + i_selected;
-            let (_, c2) = selected_batteries[i_selected];
-            if c > c2 && i <= last_index {
-                selected_batteries[i_selected] = (i, c);
-                // Drop all subsequent selections.
-                selected_batteries.resize(i_selected + 1, (0, '0'));
-                is_done = true;
-                break;
-            }
-        }
+/// The joltage of each battery in the bank, whose labels are digits in `base`.
+fn battery_joltages(battery_bank: &str, base: u32) -> Result<Vec<u8>, Error> {
+    battery_bank
+        .char_indices()
+        .map(|(start, c)| {
+            c.to_digit(base).map(|digit| digit as u8).ok_or_else(|| {
+                Error::parse_at(
+                    battery_bank,
+                    &battery_bank[start..(start + c.len_utf8())],
+                    format!("invalid battery joltage in base {}: {}", base, c),
+                )
+            })
+        })
+        .collect()
+}
 
-        if !is_done && selected_batteries.len() < num_batteries.into() {
-            selected_batteries.push((i, c));
+/// Picks the `count` digits that, kept in order, read as the largest number, and returns their
+/// indices. Of the choices that read the same, it returns the one with the earliest indices.
+///
+/// A digit is dropped whenever a larger one follows it and there are still digits to spare, so
+/// each digit is pushed and popped at most once.
+fn select_max_digits(digits: &[u8], count: usize) -> Vec<usize> {
+    let mut num_to_drop = digits.len() - count;
+    let mut selected: Vec<usize> = Vec::with_capacity(digits.len());
+    for (i, &digit) in digits.iter().enumerate() {
+        while num_to_drop > 0 && selected.last().is_some_and(|&last| digits[last] < digit) {
+            selected.pop();
+            num_to_drop -= 1;
         }
+        selected.push(i);
+    }
+    selected.truncate(count);
+    selected
+}
+
+/// Finds the batteries to turn on for the largest joltage, reading the chosen batteries' labels
+/// as the digits of a number in `base`.
+fn select_max_joltage(
+    battery_bank: &str,
+    num_batteries: usize,
+    base: u32,
+) -> Result<BatteryBankSelection, Error> {
+    let joltages = battery_joltages(battery_bank, base)?;
+    if joltages.len() < num_batteries {
+        return Err(Error::LogicError(format!(
+            "bank {} has fewer than {} batteries",
+            battery_bank, num_batteries
+        )));
     }
 
-    let joltage = selected_batteries.iter().fold(0u64, |acc, (_, c)| {
-        (acc * 10) + c.to_digit(10).unwrap() as u64
-    });
+    let batteries = select_max_digits(&joltages, num_batteries);
+    let digits: Vec<u8> = batteries.iter().map(|&i| joltages[i]).collect();
+    let joltage = BigUint::from_radix_be(&digits, base).ok_or_else(|| {
+        Error::LogicError(format!("batteries do not have joltages in base {}", base))
+    })?;
+    Ok(BatteryBankSelection::new(batteries, joltage))
+}
 
-    BatteryBankSelection::new(
-        selected_batteries.iter().map(|(i, _)| *i).collect(),
-        joltage,
-    )
+/// Selects the batteries for every bank, positioning errors within `banks`.
+fn select_all(
+    banks: &str,
+    num_batteries_per_bank: usize,
+    base: u32,
+) -> Result<Vec<BatteryBankSelection>, Error> {
+    banks
+        .lines()
+        .map(|bank| {
+            select_max_joltage(bank, num_batteries_per_bank, base)
+                .map_err(|err| err.within(banks, bank))
+        })
+        .collect()
 }
 
-/// Finds the best total joltage across all the battery banks by taking the
-pub fn find_best_total_joltage(banks: &str, num_batteries_per_bank: u8) -> u64 {
-    let mut joltage = 0u64;
-    for (i, bank) in banks.lines().enumerate() {
-        let selection = select_max_joltage(bank, num_batteries_per_bank);
+/// Finds the best total joltage across all the battery banks by taking the best from each.
+pub fn find_best_total_joltage(
+    banks: &str,
+    num_batteries_per_bank: usize,
+    base: u32,
+) -> Result<BigUint, Error> {
+    let mut joltage = BigUint::ZERO;
+    for (i, selection) in select_all(banks, num_batteries_per_bank, base)?
+        .into_iter()
+        .enumerate()
+    {
         log::debug!(
-            "Selected {:?} (joltage: {}) for bank {} ({})",
+            "Selected {:?} (joltage: {}) for bank {}",
             selection.batteries,
             selection.joltage,
-            i,
-            bank
+            i
         );
         joltage += selection.joltage;
     }
-    joltage
+    Ok(joltage)
+}
+
+/// A row per bank, with the batteries turned on and the joltage they give.
+fn explain_selections(
+    banks: &str,
+    num_batteries_per_bank: usize,
+    base: u32,
+) -> Result<Explanation, Error> {
+    let selections = select_all(banks, num_batteries_per_bank, base)?;
+    let rows = banks
+        .lines()
+        .zip(selections)
+        .enumerate()
+        .map(|(i, (bank, selection))| {
+            let batteries: Vec<String> = selection.batteries.iter().map(usize::to_string).collect();
+            let labels: String = selection
+                .batteries
+                .iter()
+                .map(|&battery| &bank[battery..=battery])
+                .collect();
+            vec![
+                (i + 1).to_string(),
+                batteries.join(","),
+                labels,
+                selection.joltage.to_string(),
+            ]
+        })
+        .collect();
+    Ok(Explanation {
+        columns: vec!["Bank", "Batteries", "Labels", "Joltage"],
+        rows,
+    })
+}
+
+/// Checks that every battery is labelled with a digit in some base, so that banks can be split
+/// into batteries by character. Whether the digits fit the chosen base is checked when solving.
+fn parse_banks(input: &str) -> Result<String, Error> {
+    for (start, c) in input.char_indices() {
+        if c != '\n' && !c.is_ascii_alphanumeric() {
+            return Err(Error::parse_at(
+                input,
+                &input[start..(start + c.len_utf8())],
+                format!("invalid battery joltage: {}", c),
+            ));
+        }
+    }
+    Ok(input.to_string())
+}
+
+/// The number of batteries to turn on and the base of their labels.
+fn battery_params(params: &Params) -> Result<(usize, u32), Error> {
+    let base = params.get("base")?;
+    if !(2..=MAX_BASE as usize).contains(&base) {
+        return Err(Error::LogicError(format!(
+            "base must be from 2 to {}, got {}",
+            MAX_BASE, base
+        )));
+    }
+    let batteries = params.get("batteries")?;
+    if batteries == 0 {
+        return Err(Error::LogicError(
+            "batteries must be at least 1, got 0".to_string(),
+        ));
+    }
+    Ok((batteries, base as u32))
+}
+
+pub struct Day3;
+
+impl Solver for Day3 {
+    /// The banks are kept as text, since each part selects a different number of batteries.
+    type Parsed = String;
+
+    const PARAMS: &'static [Param] = &[
+        Param {
+            name: "batteries",
+            description: "batteries to turn on in each bank",
+            default: 2,
+            overrides: &[ParamOverride {
+                part: Some(Part::Two),
+                dataset: None,
+                value: 12,
+            }],
+        },
+        Param {
+            name: "base",
+            description: "base of the battery labels, up to 36 with a-z after 9",
+            default: 10,
+            overrides: &[],
+        },
+    ];
+
+    fn parse(&self, input: &str) -> Result<String, Error> {
+        parse_banks(input)
+    }
+
+    fn part1(&self, banks: &String, params: &Params) -> Result<Answer, Error> {
+        let (num_batteries, base) = battery_params(params)?;
+        let joltage = find_best_total_joltage(banks, num_batteries, base)?;
+        Ok(BigInt::from(joltage).into())
+    }
+
+    fn part2(&self, banks: &String, params: &Params) -> Result<Answer, Error> {
+        let (num_batteries, base) = battery_params(params)?;
+        let joltage = find_best_total_joltage(banks, num_batteries, base)?;
+        Ok(BigInt::from(joltage).into())
+    }
+
+    fn explain(
+        &self,
+        banks: &String,
+        _part: Part,
+        params: &Params,
+    ) -> Result<Option<Explanation>, Error> {
+        let (num_batteries, base) = battery_params(params)?;
+        explain_selections(banks, num_batteries, base).map(Some)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
+    use crate::utils::input::error_position;
 
     #[test]
     fn select_max_joltage_one_option() {
         assert_eq!(
-            select_max_joltage("56", 2),
-            BatteryBankSelection::new(vec!(0, 1), 56)
+            select_max_joltage("56", 2, 10).unwrap(),
+            BatteryBankSelection::new(vec!(0, 1), 56u64)
         )
     }
 
     #[test]
     fn select_max_joltage_best_last() {
         assert_eq!(
-            select_max_joltage("123456", 2),
-            BatteryBankSelection::new(vec!(4, 5), 56)
+            select_max_joltage("123456", 2, 10).unwrap(),
+            BatteryBankSelection::new(vec!(4, 5), 56u64)
         );
 
         assert_eq!(
-            select_max_joltage("111234559", 6),
-            BatteryBankSelection::new(vec!(3, 4, 5, 6, 7, 8), 234559)
+            select_max_joltage("111234559", 6, 10).unwrap(),
+            BatteryBankSelection::new(vec!(3, 4, 5, 6, 7, 8), 234559u64)
         );
     }
 
     #[test]
     fn select_max_joltage_all_same() {
-        let selection = select_max_joltage("11111", 2);
-        assert_eq!(11, selection.joltage);
+        let selection = select_max_joltage("11111", 2, 10).unwrap();
+        assert_eq!(selection.joltage, BigUint::from(11u32));
 
-        let selection = select_max_joltage("11111111", 4);
-        assert_eq!(1111, selection.joltage);
+        let selection = select_max_joltage("11111111", 4, 10).unwrap();
+        assert_eq!(selection.joltage, BigUint::from(1111u32));
     }
 
     #[test]
     fn select_max_joltage_best_first() {
         assert_eq!(
-            select_max_joltage("654321", 2),
-            BatteryBankSelection::new(vec!(0, 1), 65)
+            select_max_joltage("654321", 2, 10).unwrap(),
+            BatteryBankSelection::new(vec!(0, 1), 65u64)
         );
 
         assert_eq!(
-            select_max_joltage("54321111111", 7),
-            BatteryBankSelection::new(vec!(0, 1, 2, 3, 4, 5, 6), 5432111)
+            select_max_joltage("54321111111", 7, 10).unwrap(),
+            BatteryBankSelection::new(vec!(0, 1, 2, 3, 4, 5, 6), 5432111u64)
         );
     }
 
     #[test]
     fn select_max_joltage_mixed() {
         assert_eq!(
-            select_max_joltage("373561922", 2),
-            BatteryBankSelection::new(vec!(6, 7), 92)
+            select_max_joltage("373561922", 2, 10).unwrap(),
+            BatteryBankSelection::new(vec!(6, 7), 92u64)
         );
 
         assert_eq!(
-            select_max_joltage("39356192238781", 5),
-            BatteryBankSelection::new(vec!(1, 6, 10, 12, 13), 99881)
+            select_max_joltage("39356192238781", 5, 10).unwrap(),
+            BatteryBankSelection::new(vec!(1, 6, 10, 12, 13), 99881u64)
+        );
+    }
+
+    #[test]
+    fn select_max_joltage_short_bank() {
+        assert!(select_max_joltage("123", 4, 10).is_err());
+        assert!(find_best_total_joltage("987654321111111\n1", 2, 10).is_err());
+    }
+
+    #[test]
+    fn parse_banks_invalid_battery() {
+        assert_eq!(error_position(parse_banks("12345\n12-45")), Some((2, 3)));
+        assert!(parse_banks("12345\n67890\nabcdef").is_ok());
+    }
+
+    #[test]
+    fn battery_labels_must_fit_base() {
+        assert_eq!(
+            error_position(find_best_total_joltage("12345\n12a45", 2, 10)),
+            Some((2, 3))
         );
+        assert!(find_best_total_joltage("101\n2", 1, 2).is_err());
+    }
+
+    #[test]
+    fn select_max_joltage_other_bases() {
+        assert_eq!(
+            select_max_joltage("10110", 3, 2).unwrap(),
+            BatteryBankSelection::new(vec![0, 2, 3], 0b111u32)
+        );
+        assert_eq!(
+            select_max_joltage("3fA9e", 2, 16).unwrap(),
+            BatteryBankSelection::new(vec![1, 4], 0xfeu32)
+        );
+        assert_eq!(
+            select_max_joltage("zz0", 2, 36).unwrap().joltage,
+            BigUint::from(35u32 * 36 + 35)
+        );
+    }
+
+    #[test]
+    fn select_max_joltage_past_u64() {
+        let bank = "9".repeat(25) + "1";
+        let selection = select_max_joltage(&bank, 25, 10).unwrap();
+        assert_eq!(selection.batteries, (0..25).collect::<Vec<_>>());
+        assert_eq!(selection.joltage.to_string(), "9".repeat(25));
+    }
+
+    #[test]
+    fn select_no_batteries() {
+        assert_eq!(
+            select_max_joltage("123", 0, 10).unwrap(),
+            BatteryBankSelection::new(vec![], 0u32)
+        );
+    }
+
+    #[test]
+    fn battery_params_must_be_in_range() {
+        use crate::solver::{DynSolver, ParamSetting};
+        let input = "987654321111111\n818181911112111";
+
+        let settings: [ParamSetting; 1] = ["batteries=1".parse().unwrap()];
+        let params = Params::resolve(Day3::PARAMS, Part::One, None, &settings).unwrap();
+        assert_eq!(Day3.run(Part::One, input, &params), Ok(Answer::Integer(18)));
+
+        for setting in ["batteries=0", "base=1", "base=37"] {
+            let settings: [ParamSetting; 1] = [setting.parse().unwrap()];
+            let params = Params::resolve(Day3::PARAMS, Part::One, None, &settings).unwrap();
+            assert!(matches!(
+                Day3.run(Part::One, input, &params),
+                Err(Error::LogicError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn explain_lists_each_bank() {
+        let explanation = explain_selections("987654321111111\n818181911112111", 2, 10).unwrap();
+        assert_eq!(
+            explanation.columns,
+            ["Bank", "Batteries", "Labels", "Joltage"]
+        );
+        assert_eq!(
+            explanation.rows,
+            [["1", "0,1", "98", "98"], ["2", "6,11", "92", "92"]]
+        );
+    }
+
+    /// The digits of a number in `base` read from the batteries at `indices`.
+    fn joltage_of(digits: &[u8], indices: &[usize], base: u32) -> BigUint {
+        let digits: Vec<u8> = indices.iter().map(|&i| digits[i]).collect();
+        BigUint::from_radix_be(&digits, base).unwrap()
+    }
+
+    /// Tries every way of choosing `count` batteries, in increasing order of their indices, and
+    /// keeps the first with the largest joltage.
+    fn brute_force_max_digits(digits: &[u8], count: usize, base: u32) -> Vec<usize> {
+        fn choose(
+            digits: &[u8],
+            count: usize,
+            base: u32,
+            chosen: &mut Vec<usize>,
+            best: &mut Option<(BigUint, Vec<usize>)>,
+        ) {
+            if chosen.len() == count {
+                let joltage = joltage_of(digits, chosen, base);
+                if best.as_ref().is_none_or(|(max, _)| joltage > *max) {
+                    *best = Some((joltage, chosen.clone()));
+                }
+                return;
+            }
+            let next = chosen.last().map_or(0, |&last| last + 1);
+            for i in next..digits.len() {
+                chosen.push(i);
+                choose(digits, count, base, chosen, best);
+                chosen.pop();
+            }
+        }
+
+        let mut best = None;
+        choose(digits, count, base, &mut Vec::new(), &mut best);
+        best.map(|(_, indices)| indices).unwrap()
+    }
+
+    /// A base, and a bank of batteries labelled with digits in that base.
+    fn bank_in_base() -> impl Strategy<Value = (u32, Vec<u8>)> {
+        prop::sample::select(vec![2u32, 3, 10, 16, 36])
+            .prop_flat_map(|base| (Just(base), prop::collection::vec(0..(base as u8), 0..11)))
+    }
+
+    proptest! {
+        #[test]
+        fn select_max_joltage_matches_brute_force(
+            (base, digits) in bank_in_base(),
+            fraction in 0.0..=1.0f64,
+        ) {
+            let count = (digits.len() as f64 * fraction) as usize;
+            let bank: String = digits
+                .iter()
+                .map(|&d| char::from_digit(u32::from(d), base).unwrap())
+                .collect();
+
+            let batteries = brute_force_max_digits(&digits, count, base);
+            let joltage = joltage_of(&digits, &batteries, base);
+            prop_assert_eq!(
+                select_max_joltage(&bank, count, base).unwrap(),
+                BatteryBankSelection::new(batteries, joltage)
+            );
+        }
     }
 }
@@ -1,4 +1,8 @@
+use crate::solver::{Answer, Params, Part, Solver};
+use crate::utils::grid::{self, Location};
 use crate::utils::input::Error;
+use crate::visualize::{Cell, Frame, Style, Visualization, VisualizeOptions};
+use std::collections::HashSet;
 use std::fmt::Display;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -17,139 +21,58 @@ impl Thing {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-struct Location {
-    x: usize,
-    y: usize,
-}
-
-impl Location {
-    fn new(x: usize, y: usize) -> Location {
-        Location { x, y }
-    }
-
-    fn surrounding_locations(&self) -> Vec<Location> {
-        let min_x = 1.max(self.x) - 1;
-        let min_y = 1.max(self.y) - 1;
-        let mut surrounds = Vec::with_capacity(8);
-        for x in min_x..=(self.x + 1) {
-            for y in min_y..=(self.y + 1) {
-                if x == self.x && y == self.y {
-                    continue;
-                }
-                surrounds.push(Location::new(x, y))
-            }
-        }
-        surrounds
-    }
-}
-
-impl Display for Location {
+impl Display for Thing {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.x, self.y)
+        match self {
+            Thing::RollOfPaper => write!(f, "@"),
+            Thing::Nothing => write!(f, "."),
+        }
     }
 }
 
-struct Grid {
-    num_rows: usize,
-    num_cols: usize,
-    data: Vec<Thing>,
-}
+#[derive(Clone)]
+pub struct Grid(grid::Grid<Thing>);
 
 impl Grid {
     fn parse(grid: &str) -> Result<Grid, Error> {
-        let mut num_rows = 0;
-        let mut num_cols = 0;
-        let mut data = Vec::new();
-        let mut first_line = true;
-        for (i, line) in grid.lines().enumerate() {
-            if first_line {
-                num_cols = line.len();
-                first_line = false;
-            } else if line.len() != num_cols {
-                return Err(Error::ParseError(format!(
-                    "inconsistent number of columns on line {i}; expected {num_cols}, found {}",
-                    line.len()
-                )));
-            }
-            for c in line.chars() {
-                data.push(Thing::parse(c)?);
-            }
-            num_rows += 1;
-        }
-        Ok(Grid {
-            num_cols,
-            num_rows,
-            data,
-        })
-    }
-
-    fn set(&mut self, location: &Location, value: Thing) {
-        if let Some(index) = self.to_index(location) {
-            self.data[index] = value;
-        }
+        grid::Grid::parse(grid, Thing::parse).map(Grid)
     }
 
     fn at(&self, location: &Location) -> Thing {
-        let index = self.to_index(location);
-        match index {
-            Some(idx) => self.data[idx],
-            None => Thing::Nothing,
-        }
-    }
-
-    fn to_index(&self, location: &Location) -> Option<usize> {
-        if location.x >= self.num_cols || location.y >= self.num_rows {
-            None
-        } else {
-            Some(location.y * self.num_cols + location.x)
-        }
+        self.0.get(location).copied().unwrap_or(Thing::Nothing)
     }
 
     fn is_accessible(&self, location: &Location) -> bool {
-        if self.to_index(location).is_none() {
+        if !self.0.contains(location) {
             return false;
         }
 
-        let num_rolls_of_paper =
-            location
-                .surrounding_locations()
-                .into_iter()
-                .fold(0u8, |acc, l| {
-                    if self.at(&l) == Thing::RollOfPaper {
-                        acc + 1
-                    } else {
-                        acc
-                    }
-                });
+        let num_rolls_of_paper = self
+            .0
+            .surrounding_neighbours(*location)
+            .filter(|l| self.at(l) == Thing::RollOfPaper)
+            .count();
         num_rolls_of_paper < 4
     }
 }
 
 fn find_accessible_rolls_of_paper(grid: &Grid) -> Vec<Location> {
     let mut accessible = Vec::new();
-    for x in 0..grid.num_cols {
-        for y in 0..grid.num_rows {
-            let location = Location::new(x, y);
-            if grid.at(&location) == Thing::RollOfPaper && grid.is_accessible(&location) {
-                log::trace!("Found accessible roll of paper at {}", location);
-                accessible.push(location);
-            }
+    for location in grid.0.locations() {
+        if grid.at(&location) == Thing::RollOfPaper && grid.is_accessible(&location) {
+            log::trace!("Found accessible roll of paper at {}", location);
+            accessible.push(location);
         }
     }
     accessible
 }
 
-pub fn count_accessible_rolls_of_paper(grid_str: &str) -> Result<usize, Error> {
-    let grid = Grid::parse(grid_str)?;
-
-    let accessible = find_accessible_rolls_of_paper(&grid);
-
-    Ok(accessible.len())
+pub fn count_accessible_rolls_of_paper(grid: &Grid) -> usize {
+    find_accessible_rolls_of_paper(grid).len()
 }
 
-pub fn count_total_removable_rolls_of_paper(grid_str: &str) -> Result<usize, Error> {
-    let mut grid = Grid::parse(grid_str)?;
+pub fn count_total_removable_rolls_of_paper(grid: &Grid) -> usize {
+    let mut grid = grid.clone();
 
     let mut total_removed = 0;
     loop {
@@ -159,18 +82,121 @@ pub fn count_total_removable_rolls_of_paper(grid_str: &str) -> Result<usize, Err
         }
         total_removed += accessible.len();
         for l in accessible {
-            grid.set(&l, Thing::Nothing);
+            grid.0.set(&l, Thing::Nothing);
         }
     }
 
-    Ok(total_removed)
+    total_removed
+}
+
+/// Draws the grid, marking rolls of paper about to be removed and those already removed.
+fn draw(
+    grid: &Grid,
+    removing: &HashSet<Location>,
+    removed: &HashSet<Location>,
+    caption: String,
+) -> Frame {
+    let cells = grid.0.map(|location, thing| {
+        if removing.contains(&location) {
+            Cell::new('x', Style::Highlighted)
+        } else if removed.contains(&location) {
+            Cell::new('x', Style::Removed)
+        } else {
+            match thing {
+                Thing::RollOfPaper => Cell::new('@', Style::Solid),
+                Thing::Nothing => Cell::new('.', Style::Empty),
+            }
+        }
+    });
+    Frame { caption, cells }
+}
+
+fn accessible_frame(grid: &Grid) -> Frame {
+    let accessible: HashSet<Location> = find_accessible_rolls_of_paper(grid).into_iter().collect();
+    let caption = format!("{} rolls of paper are accessible", accessible.len());
+    draw(grid, &accessible, &HashSet::new(), caption)
+}
+
+/// One frame per pass of [`count_total_removable_rolls_of_paper`], then one of what is left.
+fn removal_frames(grid: &Grid) -> Vec<Frame> {
+    let mut grid = grid.clone();
+    let mut removed = HashSet::new();
+    let mut frames = Vec::new();
+    loop {
+        let accessible: HashSet<Location> =
+            find_accessible_rolls_of_paper(&grid).into_iter().collect();
+        if accessible.is_empty() {
+            break;
+        }
+        let caption = format!(
+            "Pass {}: removing {} rolls of paper",
+            frames.len() + 1,
+            accessible.len()
+        );
+        frames.push(draw(&grid, &accessible, &removed, caption));
+        for l in accessible {
+            grid.0.set(&l, Thing::Nothing);
+            removed.insert(l);
+        }
+    }
+    let caption = format!(
+        "Removed {} rolls of paper in {} passes",
+        removed.len(),
+        frames.len()
+    );
+    frames.push(draw(&grid, &HashSet::new(), &removed, caption));
+    frames
+}
+
+pub struct Day4;
+
+impl Solver for Day4 {
+    type Parsed = Grid;
+
+    fn parse(&self, input: &str) -> Result<Grid, Error> {
+        Grid::parse(input)
+    }
+
+    fn part1(&self, grid: &Grid, _params: &Params) -> Result<Answer, Error> {
+        Ok(count_accessible_rolls_of_paper(grid).into())
+    }
+
+    fn part2(&self, grid: &Grid, _params: &Params) -> Result<Answer, Error> {
+        Ok(count_total_removable_rolls_of_paper(grid).into())
+    }
+
+    fn visualize(
+        &self,
+        grid: &Grid,
+        part: Part,
+        _options: &VisualizeOptions,
+    ) -> Option<Visualization> {
+        let frames = match part {
+            Part::One => vec![accessible_frame(grid)],
+            Part::Two => removal_frames(grid),
+        };
+        Some(Visualization::Frames(frames))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
+    use proptest::prelude::*;
 
     use super::*;
+    use crate::utils::input::error_position;
+
+    /// The example from the puzzle description.
+    const EXAMPLE: &str = "..@@.@@@@.
+@@@.@.@.@@
+@@@@@.@.@@
+@.@@@@..@.
+@@.@@@@.@@
+.@@@@@@@.@
+.@.@.@.@@@
+@.@@@.@@@@
+.@@@@@@@@.
+@.@.@@@.@.";
 
     #[test]
     fn parse_thing_success() {
@@ -195,8 +221,8 @@ mod tests {
         assert!(result.is_ok());
 
         let grid = result.unwrap();
-        assert_eq!(grid.num_cols, 3);
-        assert_eq!(grid.num_rows, 4);
+        assert_eq!(grid.0.num_cols(), 3);
+        assert_eq!(grid.0.num_rows(), 4);
         assert_eq!(grid.at(&Location::new(1, 0)), Thing::Nothing);
         assert_eq!(grid.at(&Location::new(2, 0)), Thing::RollOfPaper);
         assert_eq!(grid.at(&Location::new(3, 0)), Thing::Nothing);
@@ -219,66 +245,26 @@ mod tests {
 @@@",
         );
         assert!(result.is_err());
+        assert_eq!(error_position(result), Some((1, 3)));
     }
 
     #[test]
-    fn location_surrounding_locations_all() {
-        let result = Location::new(3, 3).surrounding_locations();
-        let as_set: HashSet<Location> = result.into_iter().collect();
-        assert_eq!(
-            as_set,
-            [
-                Location::new(2, 2),
-                Location::new(2, 3),
-                Location::new(2, 4),
-                Location::new(3, 2),
-                Location::new(3, 4),
-                Location::new(4, 2),
-                Location::new(4, 3),
-                Location::new(4, 4),
-            ]
-            .into_iter()
-            .collect()
-        );
-    }
-
-    #[test]
-    fn location_surrounding_locations_top_left() {
-        let result = Location::new(0, 0).surrounding_locations();
-        let as_set: HashSet<Location> = result.into_iter().collect();
-        assert_eq!(
-            as_set,
-            [
-                Location::new(0, 1),
-                Location::new(1, 0),
-                Location::new(1, 1),
-            ]
-            .into_iter()
-            .collect()
-        );
+    fn grid_displays_as_input() {
+        let input = "..@
+...
+@@@";
+        assert_eq!(Grid::parse(input).unwrap().0.to_string(), input);
     }
 
     #[test]
     fn count_accessible_rolls_of_paper_basic() {
-        let result = count_accessible_rolls_of_paper(
-            "..@@.@@@@.
-@@@.@.@.@@
-@@@@@.@.@@
-@.@@@@..@.
-@@.@@@@.@@
-.@@@@@@@.@
-.@.@.@.@@@
-@.@@@.@@@@
-.@@@@@@@@.
-@.@.@@@.@.",
-        );
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), 13);
+        let grid = Grid::parse(EXAMPLE).unwrap();
+        assert_eq!(count_accessible_rolls_of_paper(&grid), 13);
     }
 
     #[test]
     fn count_accessible_rolls_of_paper_no_rolls() {
-        let result = count_accessible_rolls_of_paper(
+        let grid = Grid::parse(
             "..........
 ..........
 ..........
@@ -289,26 +275,74 @@ mod tests {
 ..........
 ..........
 ..........",
-        );
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), 0);
+        )
+        .unwrap();
+        assert_eq!(count_accessible_rolls_of_paper(&grid), 0);
     }
 
     #[test]
     fn count_total_removable_rolls_of_paper_basic() {
-        let result = count_total_removable_rolls_of_paper(
-            "..@@.@@@@.
-@@@.@.@.@@
-@@@@@.@.@@
-@.@@@@..@.
-@@.@@@@.@@
-.@@@@@@@.@
-.@.@.@.@@@
-@.@@@.@@@@
-.@@@@@@@@.
-@.@.@@@.@.",
+        let grid = Grid::parse(EXAMPLE).unwrap();
+        assert_eq!(count_total_removable_rolls_of_paper(&grid), 43);
+    }
+
+    #[test]
+    fn removal_frames_show_each_pass() {
+        let grid = Grid::parse(EXAMPLE).unwrap();
+        let frames = removal_frames(&grid);
+
+        assert_eq!(frames.len(), 10);
+        assert_eq!(frames[0].caption, "Pass 1: removing 13 rolls of paper");
+        assert_eq!(
+            frames[0].to_text(),
+            accessible_frame(&grid)
+                .to_text()
+                .replace("13 rolls of paper are accessible", &frames[0].caption)
         );
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), 43);
+        let last = frames.last().unwrap();
+        assert_eq!(last.caption, "Removed 43 rolls of paper in 9 passes");
+        let num_removed = last
+            .cells
+            .iter()
+            .filter(|(_, cell)| cell.style == Style::Removed)
+            .count();
+        assert_eq!(num_removed, 43);
+    }
+
+    /// A grid of rolls of paper, as it would be in the input.
+    fn grid_input() -> impl Strategy<Value = String> {
+        (1usize..12, 1usize..12).prop_flat_map(|(num_cols, num_rows)| {
+            let row = prop::collection::vec(prop::bool::weighted(0.6), num_cols).prop_map(|row| {
+                row.into_iter()
+                    .map(|is_roll| if is_roll { '@' } else { '.' })
+                    .collect::<String>()
+            });
+            prop::collection::vec(row, num_rows).prop_map(|rows| rows.join("\n"))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn total_removable_rolls_of_paper_does_not_depend_on_order(
+            input in grid_input(),
+            picks in prop::collection::vec(any::<prop::sample::Index>(), 150),
+        ) {
+            let grid = Grid::parse(&input).unwrap();
+
+            // Remove one accessible roll at a time, choosing which with `picks`. Removing a roll
+            // only makes its neighbours more accessible, so every order removes the same rolls.
+            let mut removing = grid.clone();
+            let mut total_removed = 0;
+            for pick in picks.iter().cycle() {
+                let accessible = find_accessible_rolls_of_paper(&removing);
+                if accessible.is_empty() {
+                    break;
+                }
+                removing.0.set(pick.get(&accessible), Thing::Nothing);
+                total_removed += 1;
+            }
+
+            prop_assert_eq!(count_total_removable_rolls_of_paper(&grid), total_removed);
+        }
     }
 }
@@ -1,246 +1,514 @@
-use crate::utils::input::Error;
-use std::cmp::{Ordering, max, min};
-
-#[derive(Debug, PartialEq, Eq, PartialOrd)]
-struct Range(u64, u64);
+use crate::error::AppError;
+use crate::solver::{Answer, Param, Params, Solver};
+use crate::utils::input::{self, Error};
+use crate::utils::intervals::IntervalSet;
+use std::fmt::{self, Display};
+use std::io::{self, BufRead};
+use std::ops::RangeInclusive;
+
+/// How to treat a range that starts after it ends, like `9-3`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeMode {
+    /// Reject the input.
+    Strict,
+    /// Swap the ends, so `9-3` means `3-9`.
+    Lenient,
+}
 
-impl Range {
-    fn parse(input: &str) -> Result<Range, Error> {
-        let values: Vec<&str> = input.split('-').collect();
-        if values.len() != 2 {
-            return Err(Error::ParseError(format!("invalid range: {}", input)));
-        }
-        let start = values[0]
-            .parse()
-            .map_err(|err| Error::ParseError(format!("could not parse start: {:?}", err)))?;
-        let end = values[1]
-            .parse()
-            .map_err(|err| Error::ParseError(format!("could not parse end: {:?}", err)))?;
-        Ok(Range(start, end))
-    }
-
-    fn compare_value(&self, value: u64) -> Ordering {
-        if value > self.1 {
-            Ordering::Greater
-        } else if value < self.0 {
-            Ordering::Less
-        } else {
-            Ordering::Equal
+impl RangeMode {
+    /// The mode chosen by the `lenient` param.
+    pub fn from_params(params: &Params) -> Result<RangeMode, Error> {
+        match params.get("lenient")? {
+            0 => Ok(RangeMode::Strict),
+            1 => Ok(RangeMode::Lenient),
+            value => Err(Error::LogicError(format!(
+                "lenient must be 0 or 1, got {}",
+                value
+            ))),
         }
     }
+}
 
-    fn overlaps(&self, other: &Range) -> bool {
-        !(self.0 > other.1 || self.1 < other.0)
-    }
-
-    fn merge(&mut self, other: &Range) {
-        self.0 = min(self.0, other.0);
-        self.1 = max(self.1, other.1);
+/// Parses an `a-b` range, putting its ends in order. Whether they were out of order is returned
+/// as an error pointing at the range, for strict mode to report.
+fn parse_range(input: &str) -> Result<(RangeInclusive<u64>, Option<Error>), Error> {
+    let (start, end) = input::parse_range(input)?.into_inner();
+    if start > end {
+        let inverted = Error::parse_at(input, input, "range must not start after it ends");
+        return Ok((end..=start, Some(inverted)));
     }
+    Ok((start..=end, None))
 }
 
-fn is_in_ranges(value: u64, ordered_ranges: &[Range]) -> bool {
-    let find_result = ordered_ranges.binary_search_by(|range| range.compare_value(value).reverse());
-    find_result.is_ok()
+/// The fresh ids, and an error for the first range that was written the wrong way round.
+fn parse_fresh_ranges(input: &str) -> Result<(IntervalSet<u64>, Option<Error>), Error> {
+    let ranges = input::parse_lines(input, |line| {
+        parse_range(line)
+            .map(|(range, inverted)| (range, inverted.map(|err| err.within(input, line))))
+    })?;
+    let mut first_inverted = None;
+    let fresh = ranges
+        .into_iter()
+        .map(|(range, inverted)| {
+            first_inverted = first_inverted.take().or(inverted);
+            range
+        })
+        .collect();
+    Ok((fresh, first_inverted))
 }
 
-fn parse_ordered_ranges(input: &str) -> Result<Vec<Range>, Error> {
-    let ranges_result: Result<Vec<Range>, Error> = input.lines().map(Range::parse).collect();
-    let mut ranges = ranges_result?;
+/// The fresh ids, followed by the ingredient ids to check.
+pub struct Ingredients {
+    /// Every fresh id, with inverted ranges swapped round.
+    fresh: IntervalSet<u64>,
+    /// Where the first inverted range is, if any.
+    inverted: Option<Error>,
+    ids: Vec<u64>,
+}
 
-    let mut did_merge = true;
-    while did_merge {
-        did_merge = false;
-        let mut i = 0;
-        while i < ranges.len() {
-            let mut j = i + 1;
-            while j < ranges.len() {
-                if ranges[i].overlaps(&ranges[j]) {
-                    let old_range = ranges.swap_remove(j);
-                    ranges[i].merge(&old_range);
-                    did_merge = true;
-                } else {
-                    j += 1;
-                }
-            }
-            i += 1;
+impl Ingredients {
+    /// The fresh ids, or an error if strict and some range was inverted.
+    fn fresh(&self, mode: RangeMode) -> Result<&IntervalSet<u64>, Error> {
+        match (&self.inverted, mode) {
+            (Some(err), RangeMode::Strict) => Err(err.clone()),
+            _ => Ok(&self.fresh),
         }
     }
-
-    ranges.sort_unstable_by_key(|r| r.0);
-    Ok(ranges)
 }
 
-fn parse_ingredients_and_ids(input: &str) -> Result<(Vec<Range>, Vec<u64>), Error> {
-    let split: Vec<&str> = input.split("\n\n").collect();
-    if split.len() != 2 {
-        return Err(Error::ParseError(format!(
-            "ingredients input must have an empty line between ranges and ids, received: {}",
-            input
-        )));
-    }
+fn parse_ingredients_and_ids(input: &str) -> Result<Ingredients, Error> {
+    let [range_strs, id_strs] = input::parse_sections(input)?;
+    let (fresh, inverted) =
+        parse_fresh_ranges(range_strs).map_err(|err| err.within(input, range_strs))?;
+    let ids = input::parse_lines(id_strs, input::parse_number)
+        .map_err(|err| err.within(input, id_strs))?;
+    Ok(Ingredients {
+        fresh,
+        inverted: inverted.map(|err| err.within(input, range_strs)),
+        ids,
+    })
+}
 
-    let ranges = parse_ordered_ranges(split[0])?;
+pub fn count_fresh_ingredients(ingredients: &Ingredients, mode: RangeMode) -> Result<u64, Error> {
+    let fresh = ingredients.fresh(mode)?;
+    Ok(ingredients
+        .ids
+        .iter()
+        .filter(|&&id| fresh.contains(id))
+        .count() as u64)
+}
 
-    let id_strs = split[1];
-    let ids: Result<Vec<u64>, Error> = id_strs
-        .split('\n')
-        .map(|id_str| {
-            id_str.parse().map_err(|err| {
-                Error::ParseError(format!("invalid ingredient id {}, err: {:?}", id_str, err))
-            })
-        })
-        .collect();
-    Ok((ranges, ids?))
+/// Counts every fresh id. This is exact even for ranges covering all of `u64`.
+pub fn count_all_fresh_ids(ingredients: &Ingredients, mode: RangeMode) -> Result<u128, Error> {
+    Ok(ingredients.fresh(mode)?.num_values())
 }
 
-pub fn count_fresh_ingredients(input: &str) -> Result<u64, Error> {
-    let (ranges, ids) = parse_ingredients_and_ids(input)?;
+/// Whether an ingredient id is in one of the fresh ranges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    Fresh,
+    Spoiled,
+}
 
-    let mut count_fresh = 0;
-    for id in ids {
-        if is_in_ranges(id, &ranges) {
-            count_fresh += 1;
+impl Display for Freshness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Freshness::Fresh => write!(f, "fresh"),
+            Freshness::Spoiled => write!(f, "spoiled"),
         }
     }
+}
 
-    Ok(count_fresh)
+/// Reads input a line at a time, cleaning up each line as `input::normalize` would.
+struct Lines<R> {
+    reader: R,
+    line: String,
+    line_number: usize,
 }
 
-pub fn count_all_fresh_ids(input: &str) -> Result<u64, Error> {
-    let range_str = input.split("\n\n").next().map_or_else(
-        || Err(Error::ParseError(format!("invalid input: {}", input))),
-        Ok,
-    )?;
-    let ranges = parse_ordered_ranges(range_str)?;
+impl<R: BufRead> Lines<R> {
+    /// The next line and its number, or None at the end of the input.
+    fn next_line(&mut self) -> Result<Option<(usize, &str)>, AppError> {
+        self.line.clear();
+        let num_read = self
+            .reader
+            .read_line(&mut self.line)
+            .map_err(|err| AppError::io("could not read input", err))?;
+        if num_read == 0 {
+            return Ok(None);
+        }
+        self.line_number += 1;
+        let mut line = self.line.as_str();
+        if self.line_number == 1 {
+            line = line.strip_prefix('\u{feff}').unwrap_or(line);
+        }
+        Ok(Some((
+            self.line_number,
+            line.trim_end_matches(['\n', '\r', ' ', '\t']),
+        )))
+    }
+}
+
+/// Counts the fresh ingredients like [`count_fresh_ingredients`], but checks each id as it is
+/// read, calling `on_id` with its freshness. Only the fresh ranges are held in memory, so the
+/// list of ids can be any length.
+///
+/// Errors are positioned at their line, but their spans are only within that line.
+pub fn stream_fresh_ingredients(
+    reader: impl BufRead,
+    mode: RangeMode,
+    mut on_id: impl FnMut(u64, Freshness) -> io::Result<()>,
+) -> Result<u64, AppError> {
+    let mut lines = Lines {
+        reader,
+        line: String::new(),
+        line_number: 0,
+    };
+
+    let mut ranges = Vec::new();
+    loop {
+        let Some((line_number, line)) = lines.next_line()? else {
+            return Err(Error::ParseError(
+                "expected 2 sections separated by blank lines, found 1".to_string(),
+            )
+            .into());
+        };
+        if line.is_empty() {
+            break;
+        }
+        let (range, inverted) = parse_range(line).map_err(|err| err.on_line(line, line_number))?;
+        if let (Some(err), RangeMode::Strict) = (inverted, mode) {
+            return Err(err.on_line(line, line_number).into());
+        }
+        ranges.push(range);
+    }
+    let fresh = IntervalSet::from_ranges(ranges);
 
     let mut count = 0;
-    for range in ranges {
-        count += range.1 - range.0 + 1;
+    let mut after_blank_line = false;
+    while let Some((line_number, line)) = lines.next_line()? {
+        // Blank lines are only allowed at the end.
+        if line.is_empty() {
+            after_blank_line = true;
+            continue;
+        }
+        if after_blank_line {
+            return Err(Error::ParseError(
+                "expected only 2 sections separated by blank lines".to_string(),
+            )
+            .on_line(line, line_number)
+            .into());
+        }
+
+        let id = input::parse_number(line).map_err(|err| err.on_line(line, line_number))?;
+        let freshness = if fresh.contains(id) {
+            count += 1;
+            Freshness::Fresh
+        } else {
+            Freshness::Spoiled
+        };
+        on_id(id, freshness).map_err(|err| AppError::io("could not write id", err))?;
     }
     Ok(count)
 }
 
+pub struct Day5;
+
+impl Solver for Day5 {
+    type Parsed = Ingredients;
+
+    const PARAMS: &'static [Param] = &[Param {
+        name: "lenient",
+        description: "1 to swap the ends of ranges like 9-3, 0 to reject them",
+        default: 0,
+        overrides: &[],
+    }];
+
+    fn parse(&self, input: &str) -> Result<Ingredients, Error> {
+        parse_ingredients_and_ids(input)
+    }
+
+    fn part1(&self, ingredients: &Ingredients, params: &Params) -> Result<Answer, Error> {
+        count_fresh_ingredients(ingredients, RangeMode::from_params(params)?).map(Answer::from)
+    }
+
+    fn part2(&self, ingredients: &Ingredients, params: &Params) -> Result<Answer, Error> {
+        count_all_fresh_ids(ingredients, RangeMode::from_params(params)?).map(Answer::from)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
+    use crate::utils::input::error_position;
 
     #[test]
-    fn test_contains() {
-        let range = Range(5, 8);
-        assert_eq!(range.compare_value(4), Ordering::Less);
-        assert_eq!(range.compare_value(5), Ordering::Equal);
-        assert_eq!(range.compare_value(6), Ordering::Equal);
-        assert_eq!(range.compare_value(8), Ordering::Equal);
-        assert_eq!(range.compare_value(9), Ordering::Greater);
+    fn test_parse_fresh_ranges() {
+        let ranges_result = parse_fresh_ranges(
+            "1-1
+6-9
+3-7
+13-15
+10-11",
+        );
+        let (fresh, inverted) = ranges_result.unwrap();
+        assert_eq!(fresh.ranges(), [1..=1, 3..=11, 13..=15]);
+        assert!(inverted.is_none());
     }
 
     #[test]
-    fn test_overlaps_sub_range() {
-        assert!(Range(4, 7).overlaps(&Range(3, 8)));
+    fn test_count_fresh_ingredients() {
+        let ingredients = parse_ingredients_and_ids(
+            "3-5
+10-14
+16-20
+12-18
+
+1
+5
+8
+11
+17
+32",
+        );
+        assert!(ingredients.is_ok());
+        assert_eq!(
+            count_fresh_ingredients(&ingredients.unwrap(), RangeMode::Strict),
+            Ok(3)
+        );
     }
 
     #[test]
-    fn test_overlaps_super_range() {
-        assert!(Range(3, 8).overlaps(&Range(4, 7)));
+    fn test_parse_range_error_position() {
+        let err = parse_range("10-1x").unwrap_err();
+        assert_eq!(err.position().map(|p| p.span.clone()), Some(3..5));
     }
 
     #[test]
-    fn test_overlaps_low_overlap() {
-        assert!(Range(3, 8).overlaps(&Range(2, 5)));
-        assert!(Range(3, 8).overlaps(&Range(2, 3)));
+    fn test_parse_ingredients_error_position() {
+        assert_eq!(
+            error_position(parse_ingredients_and_ids("3-5\n10-x4\n\n1\n5")),
+            Some((2, 4))
+        );
+
+        assert_eq!(
+            error_position(parse_ingredients_and_ids("3-5\n10-14\n\n1\nfive")),
+            Some((5, 1))
+        );
     }
 
     #[test]
-    fn test_overlaps_high_overlap() {
-        assert!(Range(5, 8).overlaps(&Range(6, 9)));
-        assert!(Range(5, 8).overlaps(&Range(8, 9)));
+    fn test_parse_ingredients_missing_separator_omits_input() {
+        let err = parse_ingredients_and_ids("3-5\n10-14\n1\n5").err().unwrap();
+        assert!(!err.to_string().contains("10-14"));
     }
 
     #[test]
-    fn test_no_overlap() {
-        assert!(!Range(5, 8).overlaps(&Range(1, 4)));
-        assert!(!Range(5, 8).overlaps(&Range(9, 11)));
+    fn test_parse_range_reversed() {
+        let (range, inverted) = parse_range("5-3").unwrap();
+        assert_eq!(range, 3..=5);
+        assert_eq!(
+            inverted.unwrap().position().map(|p| p.span.clone()),
+            Some(0..3)
+        );
+        assert_eq!(parse_range("3-3").unwrap(), (3..=3, None));
     }
 
     #[test]
-    fn test_merge_same() {
-        let mut range = Range(1, 4);
-        range.merge(&Range(1, 4));
-        assert_eq!(range, Range(1, 4));
+    fn test_inverted_ranges_strict_or_lenient() {
+        let ingredients = parse_ingredients_and_ids("3-5\n14-10\n20-16\n\n4\n12\n18").unwrap();
+
+        assert_eq!(
+            error_position(count_fresh_ingredients(&ingredients, RangeMode::Strict)),
+            Some((2, 1))
+        );
+        assert!(count_all_fresh_ids(&ingredients, RangeMode::Strict).is_err());
+
+        assert_eq!(
+            count_fresh_ingredients(&ingredients, RangeMode::Lenient),
+            Ok(3)
+        );
+        assert_eq!(
+            count_all_fresh_ids(&ingredients, RangeMode::Lenient),
+            Ok(13)
+        );
     }
 
     #[test]
-    fn test_merge_up() {
-        let mut range = Range(1, 4);
-        range.merge(&Range(3, 8));
-        assert_eq!(range, Range(1, 8));
+    fn test_count_all_fresh_ids_is_exact() {
+        let ingredients = parse_ingredients_and_ids("0-18446744073709551615\n\n1").unwrap();
+        assert_eq!(
+            count_all_fresh_ids(&ingredients, RangeMode::Strict),
+            Ok(1 << 64)
+        );
+
+        let ingredients = parse_ingredients_and_ids("18446744073709551615-0\n5-10\n\n1").unwrap();
+        assert_eq!(
+            count_all_fresh_ids(&ingredients, RangeMode::Lenient),
+            Ok(1 << 64)
+        );
     }
 
     #[test]
-    fn test_merge_down() {
-        let mut range = Range(3, 5);
-        range.merge(&Range(1, 4));
-        assert_eq!(range, Range(1, 5));
+    fn test_solve_with_crlf_and_trailing_newline() {
+        use crate::solver::{DynSolver, Part};
+        let params = Params::resolve(Day5::PARAMS, Part::One, None, &[]).unwrap();
+
+        let input = "3-5\r\n10-14\r\n16-20\r\n12-18\r\n\r\n1\r\n5\r\n8\r\n11\r\n17\r\n32\r\n";
+        assert_eq!(Day5.run(Part::One, input, &params), Ok(Answer::Integer(3)));
+        assert_eq!(Day5.run(Part::Two, input, &params), Ok(Answer::Integer(14)));
     }
 
     #[test]
-    fn test_merge_sub_range() {
-        let mut range = Range(2, 6);
-        range.merge(&Range(3, 4));
-        assert_eq!(range, Range(2, 6));
+    fn test_solve_with_lenient_param() {
+        use crate::solver::{DynSolver, ParamSetting, Part};
+        let input = "5-3\n10-14\n\n4\n11";
+
+        let params = Params::resolve(Day5::PARAMS, Part::One, None, &[]).unwrap();
+        assert!(Day5.run(Part::One, input, &params).is_err());
+
+        let settings: [ParamSetting; 1] = ["lenient=1".parse().unwrap()];
+        let params = Params::resolve(Day5::PARAMS, Part::One, None, &settings).unwrap();
+        assert_eq!(Day5.run(Part::One, input, &params), Ok(Answer::Integer(2)));
+        assert_eq!(Day5.run(Part::Two, input, &params), Ok(Answer::Integer(8)));
+
+        let settings: [ParamSetting; 1] = ["lenient=2".parse().unwrap()];
+        let params = Params::resolve(Day5::PARAMS, Part::One, None, &settings).unwrap();
+        assert!(matches!(
+            Day5.run(Part::One, input, &params),
+            Err(Error::LogicError(_))
+        ));
     }
 
     #[test]
-    fn test_merge_super_range() {
-        let mut range = Range(2, 6);
-        range.merge(&Range(1, 7));
-        assert_eq!(range, Range(1, 7));
+    fn test_stream_matches_parsed_count() {
+        let input =
+            "\u{feff}3-5\r\n10-14 \r\n16-20\r\n12-18\r\n\r\n1\r\n5\r\n8\r\n11\r\n17\r\n32\r\n\r\n";
+        let mut seen = Vec::new();
+        let count =
+            stream_fresh_ingredients(input.as_bytes(), RangeMode::Strict, |id, freshness| {
+                seen.push(format!("{}: {}", id, freshness));
+                Ok(())
+            });
+
+        assert_eq!(count, Ok(3));
+        assert_eq!(
+            seen,
+            [
+                "1: spoiled",
+                "5: fresh",
+                "8: spoiled",
+                "11: fresh",
+                "17: fresh",
+                "32: spoiled"
+            ]
+        );
     }
 
-    #[test]
-    fn test_is_in_ranges() {
-        let ranges = vec![Range(1, 7), Range(9, 10), Range(12, 15)];
+    fn stream(input: &str, mode: RangeMode) -> Result<u64, AppError> {
+        stream_fresh_ingredients(input.as_bytes(), mode, |_, _| Ok(()))
+    }
 
-        assert!(is_in_ranges(3, &ranges));
-        assert!(is_in_ranges(9, &ranges));
-        assert!(!is_in_ranges(8, &ranges));
-        assert!(!is_in_ranges(0, &ranges));
-        assert!(!is_in_ranges(16, &ranges));
+    fn error_line(result: Result<u64, AppError>) -> Option<(usize, usize)> {
+        match result {
+            Err(AppError::Parse(err)) => err.position().map(|p| (p.line, p.column)),
+            _ => None,
+        }
     }
 
     #[test]
-    fn test_parse_ordered_ranges() {
-        let ranges_result = parse_ordered_ranges(
-            "1-1
-6-9
-3-7
-13-15
-10-11",
+    fn test_stream_inverted_ranges() {
+        let input = "3-5\n14-10\n\n4\n12";
+        assert_eq!(error_line(stream(input, RangeMode::Strict)), Some((2, 1)));
+        assert_eq!(stream(input, RangeMode::Lenient), Ok(2));
+    }
+
+    #[test]
+    fn test_stream_errors_are_positioned() {
+        assert_eq!(
+            error_line(stream("3-5\n10-x4\n\n1", RangeMode::Strict)),
+            Some((2, 4))
+        );
+        assert_eq!(
+            error_line(stream("3-5\n\n1\nfive", RangeMode::Strict)),
+            Some((4, 1))
         );
-        assert!(ranges_result.is_ok());
         assert_eq!(
-            ranges_result.unwrap(),
-            vec!(Range(1, 1), Range(3, 9), Range(10, 11), Range(13, 15))
+            error_line(stream("3-5\n\n1\n\n2", RangeMode::Strict)),
+            Some((5, 1))
         );
+        assert!(matches!(
+            stream("3-5\n10-14", RangeMode::Strict),
+            Err(AppError::Parse(Error::ParseError(_)))
+        ));
     }
 
-    #[test]
-    fn test_count_fresh_ingredients() {
-        let count_result = count_fresh_ingredients(
-            "3-5
-10-14
-16-20
-12-18
+    /// Ranges, some of them inverted, and ids to check against them.
+    fn ranges_and_ids() -> impl Strategy<Value = (Vec<(u64, u64)>, Vec<u64>)> {
+        (
+            prop::collection::vec((0u64..60, 0u64..60), 1..8),
+            prop::collection::vec(0u64..70, 1..12),
+        )
+    }
 
-1
-5
-8
-11
-17
-32",
-        );
-        assert!(count_result.is_ok());
-        assert_eq!(count_result.unwrap(), 3);
+    fn input_of(ranges: &[(u64, u64)], ids: &[u64]) -> String {
+        let ranges: Vec<String> = ranges.iter().map(|(a, b)| format!("{}-{}", a, b)).collect();
+        let ids: Vec<String> = ids.iter().map(u64::to_string).collect();
+        format!("{}\n\n{}", ranges.join("\n"), ids.join("\n"))
+    }
+
+    proptest! {
+        #[test]
+        fn test_lenient_counts_match_naive_scan((ranges, ids) in ranges_and_ids()) {
+            let ingredients = parse_ingredients_and_ids(&input_of(&ranges, &ids)).unwrap();
+            let is_fresh = |id: u64| {
+                ranges
+                    .iter()
+                    .any(|&(a, b)| (a.min(b)..=a.max(b)).contains(&id))
+            };
+
+            let fresh_ids = ids.iter().filter(|&&id| is_fresh(id)).count() as u64;
+            prop_assert_eq!(
+                count_fresh_ingredients(&ingredients, RangeMode::Lenient),
+                Ok(fresh_ids)
+            );
+            let all_fresh_ids = (0..70).filter(|&id| is_fresh(id)).count() as u128;
+            prop_assert_eq!(
+                count_all_fresh_ids(&ingredients, RangeMode::Lenient),
+                Ok(all_fresh_ids)
+            );
+        }
+
+        #[test]
+        fn test_strict_rejects_exactly_inverted_ranges((ranges, ids) in ranges_and_ids()) {
+            let ingredients = parse_ingredients_and_ids(&input_of(&ranges, &ids)).unwrap();
+            let first_inverted = ranges.iter().position(|(a, b)| a > b);
+
+            let strict = count_fresh_ingredients(&ingredients, RangeMode::Strict);
+            prop_assert_eq!(
+                strict.err().and_then(|err| err.position().map(|p| p.line)),
+                first_inverted.map(|i| i + 1)
+            );
+            if first_inverted.is_none() {
+                prop_assert_eq!(
+                    count_all_fresh_ids(&ingredients, RangeMode::Strict),
+                    count_all_fresh_ids(&ingredients, RangeMode::Lenient)
+                );
+            }
+        }
+
+        #[test]
+        fn test_stream_matches_parsed((ranges, ids) in ranges_and_ids()) {
+            let input = input_of(&ranges, &ids);
+            let ingredients = parse_ingredients_and_ids(&input).unwrap();
+            for mode in [RangeMode::Strict, RangeMode::Lenient] {
+                // Streamed errors only span their line, so compare where they are.
+                let parsed = count_fresh_ingredients(&ingredients, mode).map_err(AppError::from);
+                prop_assert_eq!(stream(&input, mode).ok(), parsed.clone().ok());
+                prop_assert_eq!(error_line(stream(&input, mode)), error_line(parsed));
+            }
+        }
     }
 }
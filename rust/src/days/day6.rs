@@ -1,6 +1,7 @@
 use std::str::FromStr;
 
-use crate::utils::input::Error;
+use crate::solver::{Answer, Params, Solver};
+use crate::utils::input::{Error, Whitespace};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Operator {
@@ -52,21 +53,24 @@ impl MathProblem {
         }
     }
 
-    fn add_digit_at(&mut self, digit: i8, index: usize) {
+    fn add_digit_at(&mut self, digit: i8, index: usize) -> Result<(), Error> {
         // Numbers can arrive out of order (e.g. if the last number has more sig digs).
         // Push 0s into earlier positions as placeholders.
         while index >= self.numbers.len() {
             self.numbers.push(0);
         }
-        self.numbers[index] = (self.numbers[index] * 10) + digit as i64;
+        self.numbers[index] = self.numbers[index]
+            .checked_mul(10)
+            .and_then(|n| n.checked_add(digit.into()))
+            .ok_or_else(|| Error::LogicError("number is too large".to_string()))?;
+        Ok(())
     }
 
     fn set_operator_if_unset(&mut self, operator: Operator) -> Result<(), Error> {
-        if self.operator.is_some() {
+        if let Some(existing) = self.operator {
             Err(Error::LogicError(format!(
                 "tried to set operator to {:?}, but operator already set to {:?}",
-                operator,
-                self.operator.unwrap()
+                operator, existing
             )))
         } else {
             self.operator = Some(operator);
@@ -74,22 +78,18 @@ impl MathProblem {
         }
     }
 
-    fn solve(&self) -> i64 {
-        match self.operator {
-            Some(Operator::Add) => self
-                .numbers
-                .iter()
-                .copied()
-                .reduce(i64::strict_add)
-                .unwrap_or(0),
-            Some(Operator::Multiply) => self
-                .numbers
-                .iter()
-                .copied()
-                .reduce(i64::strict_mul)
-                .unwrap_or(0),
-            None => 0,
-        }
+    fn solve(&self) -> Result<i64, Error> {
+        let combine = match self.operator {
+            Some(Operator::Add) => i64::checked_add,
+            Some(Operator::Multiply) => i64::checked_mul,
+            None => return Ok(0),
+        };
+        let Some((&first, rest)) = self.numbers.split_first() else {
+            return Ok(0);
+        };
+        rest.iter()
+            .try_fold(first, |acc, &n| combine(acc, n))
+            .ok_or_else(|| Error::LogicError(format!("result of {:?} overflows", self.numbers)))
     }
 }
 
@@ -113,10 +113,11 @@ fn parse_math_sheet(input: &str) -> Result<Vec<MathProblem>, Error> {
                 problem.set_operator_if_unset(operator)?;
                 continue;
             }
-            return Err(Error::ParseError(format!(
-                "could not parse {} as either a number or operator",
-                term
-            )));
+            return Err(Error::parse_at(
+                input,
+                term,
+                format!("could not parse {} as either a number or operator", term),
+            ));
         }
     }
     if problems
@@ -158,7 +159,7 @@ fn parse_cephalopod_math_sheet(input: &str) -> Result<Vec<MathProblem>, Error> {
                 continue;
             }
             if let Some(digit) = char.to_digit(10) {
-                problem.add_digit_at(digit as i8, num_idx);
+                problem.add_digit_at(digit as i8, num_idx)?;
                 is_all_whitespace = false;
                 continue;
             } else if let Ok(operator) = char.try_into() {
@@ -166,10 +167,17 @@ fn parse_cephalopod_math_sheet(input: &str) -> Result<Vec<MathProblem>, Error> {
                 is_all_whitespace = false;
                 continue;
             }
-            return Err(Error::ParseError(format!(
-                "received unexpected char {} at line: {}, column: {}",
-                char, dig_idx, char_idx
-            )));
+            // Only spaces are added when padding lines, so the char is in the original line.
+            let line = input.lines().nth(dig_idx).unwrap_or_default();
+            let start = line
+                .char_indices()
+                .nth(char_idx)
+                .map_or(line.len(), |(start, _)| start);
+            return Err(Error::parse_at(
+                input,
+                &line[start..(start + char.len_utf8()).min(line.len())],
+                format!("received unexpected char {}", char),
+            ));
         }
         if is_all_whitespace {
             problems.push(problem);
@@ -185,29 +193,61 @@ fn parse_cephalopod_math_sheet(input: &str) -> Result<Vec<MathProblem>, Error> {
     Ok(problems)
 }
 
-fn solve_math_sheet(problems: &[MathProblem]) -> Vec<i64> {
+fn solve_math_sheet(problems: &[MathProblem]) -> Result<Vec<i64>, Error> {
     problems.iter().map(MathProblem::solve).collect()
 }
 
+fn sum_solutions(solutions: Vec<i64>) -> Result<i64, Error> {
+    solutions
+        .into_iter()
+        .try_fold(0i64, i64::checked_add)
+        .ok_or_else(|| Error::LogicError("sum of solutions overflows".to_string()))
+}
+
 pub fn solve_and_sum_math_sheet(input: &str) -> Result<i64, Error> {
     let problems = parse_math_sheet(input)?;
-    let solutions = solve_math_sheet(&problems);
+    let solutions = solve_math_sheet(&problems)?;
     log::info!("Solved {} math problems", solutions.len());
 
-    Ok(solutions.into_iter().reduce(i64::strict_add).unwrap_or(0))
+    sum_solutions(solutions)
 }
 
 pub fn solve_and_sum_cephalopod_math_sheet(input: &str) -> Result<i64, Error> {
     let problems = parse_cephalopod_math_sheet(input)?;
-    let solutions = solve_math_sheet(&problems);
+    let solutions = solve_math_sheet(&problems)?;
     log::info!("Solved {} math problems", solutions.len());
 
-    Ok(solutions.into_iter().reduce(i64::strict_add).unwrap_or(0))
+    sum_solutions(solutions)
+}
+
+pub struct Day6;
+
+impl Solver for Day6 {
+    /// The sheet is kept as text, since the two parts read its columns differently.
+    type Parsed = String;
+
+    /// The cephalopod sheet's numbers are read down columns, which only line up with the spaces.
+    const WHITESPACE: Whitespace = Whitespace::Significant;
+
+    fn parse(&self, input: &str) -> Result<String, Error> {
+        Ok(input.to_string())
+    }
+
+    fn part1(&self, sheet: &String, _params: &Params) -> Result<Answer, Error> {
+        solve_and_sum_math_sheet(sheet).map(Answer::from)
+    }
+
+    fn part2(&self, sheet: &String, _params: &Params) -> Result<Answer, Error> {
+        solve_and_sum_cephalopod_math_sheet(sheet).map(Answer::from)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
+    use crate::utils::input::error_position;
 
     #[test]
     fn solve_math_sheet_small() {
@@ -219,7 +259,8 @@ mod tests {
 *   +   *   + ",
             )
             .unwrap(),
-        );
+        )
+        .unwrap();
 
         assert_eq!(result, vec!(33210, 490, 4243455, 401))
     }
@@ -247,7 +288,8 @@ mod tests {
 *   +   *   + ",
             )
             .unwrap(),
-        );
+        )
+        .unwrap();
 
         assert_eq!(result, vec!(8544, 625, 3253600, 1058))
     }
@@ -264,4 +306,122 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), 3263827);
     }
+
+    #[test]
+    fn parse_math_sheet_error_position() {
+        assert_eq!(
+            error_position(parse_math_sheet("123 328\n 45 6x\n*   +")),
+            Some((2, 5))
+        );
+    }
+
+    #[test]
+    fn parse_cephalopod_math_sheet_error_position() {
+        assert_eq!(
+            error_position(parse_cephalopod_math_sheet("12\n3x\n* ")),
+            Some((2, 2))
+        );
+    }
+
+    #[test]
+    fn solve_and_sum_math_sheet_overflow() {
+        let result = solve_and_sum_math_sheet("9999999999\n9999999999\n*");
+        assert!(matches!(result, Err(Error::LogicError(_))));
+    }
+
+    #[test]
+    fn solve_cephalopod_math_sheet_keeps_significant_whitespace() {
+        use crate::solver::{DynSolver, Part};
+        let params = Params::default();
+
+        let input =
+            "123 328  51 64 \r\n 45 64  387 23 \r\n  6 98  215 314\r\n*   +   *   + \r\n\r\n";
+        assert_eq!(
+            Day6.run(Part::Two, input, &params),
+            Ok(Answer::Integer(3263827))
+        );
+    }
+
+    /// One problem on a sheet: its operator, and each row's digits and whether they are aligned
+    /// to the right of the problem's columns.
+    type Block = (Operator, Vec<(Vec<u8>, bool)>);
+
+    fn sheet(num_rows: usize) -> impl Strategy<Value = Vec<Block>> {
+        let operator = prop_oneof![Just(Operator::Add), Just(Operator::Multiply)];
+        let row = (prop::collection::vec(0u8..10, 1..=4), any::<bool>());
+        prop::collection::vec((operator, prop::collection::vec(row, num_rows)), 1..6)
+    }
+
+    /// Lays out the problems side by side, a column of spaces apart, with each operator under
+    /// the first column of its problem.
+    fn write_sheet(blocks: &[Block]) -> String {
+        let num_rows = blocks[0].1.len();
+        let mut lines = vec![Vec::new(); num_rows + 1];
+        for (operator, rows) in blocks {
+            let width = rows.iter().map(|(digits, _)| digits.len()).max().unwrap();
+            for (line, (digits, right)) in lines.iter_mut().zip(rows) {
+                let digits: String = digits.iter().map(|d| d.to_string()).collect();
+                line.push(if *right {
+                    format!("{:>width$}", digits)
+                } else {
+                    format!("{:<width$}", digits)
+                });
+            }
+            let operator = if *operator == Operator::Add { '+' } else { '*' };
+            lines[num_rows].push(format!("{:<width$}", operator));
+        }
+        lines
+            .iter()
+            .map(|line| line.join(" "))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn apply(operator: Operator, numbers: impl Iterator<Item = i64>) -> i64 {
+        match operator {
+            Operator::Add => numbers.sum(),
+            Operator::Multiply => numbers.product(),
+        }
+    }
+
+    fn number(digits: impl Iterator<Item = u8>) -> i64 {
+        digits.fold(0, |n, d| n * 10 + i64::from(d))
+    }
+
+    proptest! {
+        #[test]
+        fn solve_sheets_match_rows_and_columns(blocks in (1usize..=4).prop_flat_map(sheet)) {
+            let input = write_sheet(&blocks);
+
+            let by_rows: i64 = blocks
+                .iter()
+                .map(|(operator, rows)| {
+                    let numbers = rows.iter().map(|(digits, _)| number(digits.iter().copied()));
+                    apply(*operator, numbers)
+                })
+                .sum();
+            prop_assert_eq!(solve_and_sum_math_sheet(&input), Ok(by_rows), "{}", input);
+
+            // Each column's digits are read from top to bottom, skipping the padding.
+            let by_columns: i64 = blocks
+                .iter()
+                .map(|(operator, rows)| {
+                    let width = rows.iter().map(|(digits, _)| digits.len()).max().unwrap();
+                    let columns = (0..width).map(|x| {
+                        number(rows.iter().filter_map(|(digits, right)| {
+                            let offset = if *right { width - digits.len() } else { 0 };
+                            x.checked_sub(offset).and_then(|i| digits.get(i)).copied()
+                        }))
+                    });
+                    apply(*operator, columns)
+                })
+                .sum();
+            prop_assert_eq!(
+                solve_and_sum_cephalopod_math_sheet(&input),
+                Ok(by_columns),
+                "{}",
+                input
+            );
+        }
+    }
 }
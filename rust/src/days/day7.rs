@@ -1,9 +1,12 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::utils::input::Error;
+use crate::solver::{Answer, Params, Part, Solver};
+use crate::utils::grid::{Grid, Location};
+use crate::utils::input::{self, Error};
+use crate::visualize::{Cell, Frame, Style, Visualization, VisualizeOptions};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-enum TachyonManifoldItem {
+pub enum TachyonManifoldItem {
     BeamStart,
     Splitter,
     Empty,
@@ -25,63 +28,152 @@ impl TryFrom<char> for TachyonManifoldItem {
     }
 }
 
-pub fn count_beam_splits(input: &str) -> Result<u64, Error> {
-    let mut beam_splits = 0;
-    let mut beam_idxs = HashSet::new();
+impl std::fmt::Display for TachyonManifoldItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self {
+            TachyonManifoldItem::Splitter => '^',
+            TachyonManifoldItem::BeamStart => 'S',
+            TachyonManifoldItem::Empty => '.',
+        };
+        write!(f, "{}", c)
+    }
+}
+
+pub fn parse_manifold(input: &str) -> Result<Grid<TachyonManifoldItem>, Error> {
+    input::parse_grid(input)
+}
+
+/// Moves beams down through row `y`, returning the beams entering the next row and the columns
+/// of the splitters that were hit.
+///
+/// A splitter on the left edge has nowhere to send its left beam, so hitting one is an error.
+fn next_beams(
+    y: usize,
+    row: &[TachyonManifoldItem],
+    beam_idxs: &HashSet<usize>,
+) -> Result<(HashSet<usize>, Vec<usize>), Error> {
     let mut next_beam_idxs = HashSet::new();
-    for line in input.lines() {
-        for (idx, c) in line.chars().enumerate() {
-            match TachyonManifoldItem::try_from(c)? {
-                TachyonManifoldItem::BeamStart => {
+    let mut split_idxs = Vec::new();
+    for (idx, item) in row.iter().enumerate() {
+        match item {
+            TachyonManifoldItem::BeamStart => {
+                next_beam_idxs.insert(idx);
+            }
+            TachyonManifoldItem::Empty => {
+                if beam_idxs.contains(&idx) {
                     next_beam_idxs.insert(idx);
                 }
-                TachyonManifoldItem::Empty => {
-                    if beam_idxs.contains(&idx) {
-                        next_beam_idxs.insert(idx);
-                    }
-                }
-                TachyonManifoldItem::Splitter => {
-                    if beam_idxs.contains(&idx) {
-                        beam_splits += 1;
-                        next_beam_idxs.insert(idx - 1);
-                        next_beam_idxs.insert(idx + 1);
+            }
+            TachyonManifoldItem::Splitter => {
+                if beam_idxs.contains(&idx) {
+                    if idx == 0 {
+                        return Err(Error::LogicError(format!(
+                            "beam splits off the left edge at row {}, column {}",
+                            y + 1,
+                            idx + 1
+                        )));
                     }
+                    split_idxs.push(idx);
+                    next_beam_idxs.insert(idx - 1);
+                    next_beam_idxs.insert(idx + 1);
                 }
-            };
-        }
+            }
+        };
+    }
+    Ok((next_beam_idxs, split_idxs))
+}
+
+pub fn count_beam_splits(manifold: &Grid<TachyonManifoldItem>) -> Result<u64, Error> {
+    let mut beam_splits = 0;
+    let mut beam_idxs = HashSet::new();
+    for (y, row) in manifold.rows().enumerate() {
+        let (next_beam_idxs, split_idxs) = next_beams(y, row, &beam_idxs)?;
+        beam_splits += split_idxs.len() as u64;
         beam_idxs = next_beam_idxs;
-        next_beam_idxs = HashSet::new();
     }
     Ok(beam_splits)
 }
 
-pub fn count_timelines(input: &str) -> Result<u64, Error> {
+/// One frame per row, lighting up the beams and the splitters they hit as they move down.
+///
+/// The frames stop at a splitter on the left edge, which part 1 reports as an error.
+fn beam_frames(manifold: &Grid<TachyonManifoldItem>) -> Vec<Frame> {
+    let mut cells = manifold.map(|_, item| match item {
+        TachyonManifoldItem::BeamStart => Cell::new('S', Style::Lit),
+        TachyonManifoldItem::Splitter => Cell::new('^', Style::Solid),
+        TachyonManifoldItem::Empty => Cell::new('.', Style::Empty),
+    });
+    let mut frames = Vec::with_capacity(manifold.num_rows());
+    let mut beam_splits = 0;
+    let mut beam_idxs = HashSet::new();
+    for (y, row) in manifold.rows().enumerate() {
+        for (idx, item) in row.iter().enumerate() {
+            if *item == TachyonManifoldItem::Empty && beam_idxs.contains(&idx) {
+                cells.set(&Location::new(idx, y), Cell::new('|', Style::Lit));
+            }
+        }
+        let Ok((next_beam_idxs, split_idxs)) = next_beams(y, row, &beam_idxs) else {
+            break;
+        };
+        for &idx in split_idxs.iter() {
+            cells.set(&Location::new(idx, y), Cell::new('^', Style::Highlighted));
+        }
+        beam_splits += split_idxs.len();
+        beam_idxs = next_beam_idxs;
+
+        // A beam split off the right edge is not drawn or counted.
+        let num_beams = beam_idxs
+            .iter()
+            .filter(|&&idx| idx < manifold.num_cols())
+            .count();
+        frames.push(Frame {
+            caption: format!("Row {}: {} beams, {} splits", y + 1, num_beams, beam_splits),
+            cells: cells.clone(),
+        });
+    }
+    frames
+}
+
+/// Adds `num_timelines` to the timelines of the beam at `idx`.
+fn add_timelines(
+    timelines_by_idx: &mut HashMap<usize, u64>,
+    idx: usize,
+    num_timelines: u64,
+) -> Result<(), Error> {
+    let timelines = timelines_by_idx.entry(idx).or_insert(0);
+    *timelines = timelines
+        .checked_add(num_timelines)
+        .ok_or_else(|| Error::LogicError("number of timelines overflows".to_string()))?;
+    Ok(())
+}
+
+pub fn count_timelines(manifold: &Grid<TachyonManifoldItem>) -> Result<u64, Error> {
     let mut beam_timelines_by_idx: HashMap<usize, u64> = HashMap::new();
     let mut next_beam_timelines_by_idx = HashMap::new();
-    for line in input.lines() {
-        for (idx, c) in line.chars().enumerate() {
-            match TachyonManifoldItem::try_from(c)? {
+    for row in manifold.rows() {
+        for (idx, item) in row.iter().enumerate() {
+            match item {
                 TachyonManifoldItem::BeamStart => {
                     next_beam_timelines_by_idx.insert(idx, 1);
                 }
                 TachyonManifoldItem::Empty => {
                     if let Some(&num_timelines_above) = beam_timelines_by_idx.get(&idx) {
-                        let &timelines = next_beam_timelines_by_idx.get(&idx).unwrap_or(&0);
-                        next_beam_timelines_by_idx.insert(idx, timelines + num_timelines_above);
+                        add_timelines(&mut next_beam_timelines_by_idx, idx, num_timelines_above)?;
                     }
                 }
                 TachyonManifoldItem::Splitter => {
-                    if let Some(num_timelines_above) = beam_timelines_by_idx.get(&idx) {
+                    if let Some(&num_timelines_above) = beam_timelines_by_idx.get(&idx) {
                         let mut split_idxs = Vec::with_capacity(2);
                         if idx > 0 {
                             split_idxs.push(idx - 1);
                         }
                         split_idxs.push(idx + 1);
                         for split_idx in split_idxs {
-                            let &timelines =
-                                next_beam_timelines_by_idx.get(&split_idx).unwrap_or(&0);
-                            next_beam_timelines_by_idx
-                                .insert(split_idx, timelines + num_timelines_above);
+                            add_timelines(
+                                &mut next_beam_timelines_by_idx,
+                                split_idx,
+                                num_timelines_above,
+                            )?;
                         }
                     }
                 }
@@ -90,16 +182,57 @@ pub fn count_timelines(input: &str) -> Result<u64, Error> {
         beam_timelines_by_idx = next_beam_timelines_by_idx;
         next_beam_timelines_by_idx = HashMap::new();
     }
-    Ok(beam_timelines_by_idx.values().sum())
+    beam_timelines_by_idx
+        .values()
+        .try_fold(0u64, |total, &timelines| total.checked_add(timelines))
+        .ok_or_else(|| Error::LogicError("number of timelines overflows".to_string()))
+}
+
+pub struct Day7;
+
+impl Solver for Day7 {
+    type Parsed = Grid<TachyonManifoldItem>;
+
+    fn parse(&self, input: &str) -> Result<Grid<TachyonManifoldItem>, Error> {
+        parse_manifold(input)
+    }
+
+    fn part1(
+        &self,
+        manifold: &Grid<TachyonManifoldItem>,
+        _params: &Params,
+    ) -> Result<Answer, Error> {
+        count_beam_splits(manifold).map(Answer::from)
+    }
+
+    fn part2(
+        &self,
+        manifold: &Grid<TachyonManifoldItem>,
+        _params: &Params,
+    ) -> Result<Answer, Error> {
+        count_timelines(manifold).map(Answer::from)
+    }
+
+    fn visualize(
+        &self,
+        manifold: &Grid<TachyonManifoldItem>,
+        _part: Part,
+        _options: &VisualizeOptions,
+    ) -> Option<Visualization> {
+        Some(Visualization::Frames(beam_frames(manifold)))
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
+    use crate::utils::input::error_position;
 
     #[test]
     fn count_beam_splits_small() {
-        let result = count_beam_splits(
+        let manifold = parse_manifold(
             ".......S.......
 ...............
 .......^.......
@@ -116,15 +249,16 @@ mod tests {
 ...............
 .^.^.^.^.^...^.
 ...............",
-        );
+        )
+        .unwrap();
+        let result = count_beam_splits(&manifold);
 
-        assert!(result.is_ok(), "Error: {:?}", result.err());
-        assert_eq!(result.unwrap(), 21);
+        assert_eq!(result, Ok(21));
     }
 
     #[test]
     fn count_timelines_small() {
-        let result = count_timelines(
+        let manifold = parse_manifold(
             ".......S.......
 ...............
 .......^.......
@@ -141,9 +275,170 @@ mod tests {
 ...............
 .^.^.^.^.^...^.
 ...............",
+        )
+        .unwrap();
+        let result = count_timelines(&manifold);
+
+        assert_eq!(result, Ok(40));
+    }
+
+    #[test]
+    fn parse_manifold_invalid_item() {
+        assert_eq!(error_position(parse_manifold("..S..\n..x..")), Some((2, 3)));
+    }
+
+    #[test]
+    fn count_beam_splits_at_left_edge() {
+        let manifold = parse_manifold("S..\n^..\n...").unwrap();
+        assert_eq!(
+            count_beam_splits(&manifold),
+            Err(Error::LogicError(
+                "beam splits off the left edge at row 2, column 1".to_string()
+            ))
         );
+        assert_eq!(count_timelines(&manifold), Ok(1));
+        assert_eq!(beam_frames(&manifold).len(), 1);
+    }
+
+    #[test]
+    fn count_timelines_overflow() {
+        // Every beam splits in every row, doubling the timelines far past a u64.
+        let top = format!("{}S{}", ".".repeat(100), ".".repeat(100));
+        let rows = vec!["^".repeat(201); 100];
+        let input = format!("{}\n{}", top, rows.join("\n"));
+        let manifold = parse_manifold(&input).unwrap();
+        assert!(matches!(
+            count_timelines(&manifold),
+            Err(Error::LogicError(_))
+        ));
+    }
 
-        assert!(result.is_ok(), "Error: {:?}", result.err());
-        assert_eq!(result.unwrap(), 40);
+    #[test]
+    fn count_timelines_at_right_edge() {
+        // The beam split off the right edge is lost below, but still counts in the last row.
+        let manifold = parse_manifold("..S\n..^").unwrap();
+        assert_eq!(count_beam_splits(&manifold), Ok(1));
+        assert_eq!(count_timelines(&manifold), Ok(2));
+
+        let manifold = parse_manifold("..S\n..^\n...").unwrap();
+        assert_eq!(count_beam_splits(&manifold), Ok(1));
+        assert_eq!(count_timelines(&manifold), Ok(1));
+    }
+
+    #[test]
+    fn beam_frames_light_rows_in_turn() {
+        let manifold = parse_manifold(".S.\n...\n.^.\n...").unwrap();
+        let frames = beam_frames(&manifold);
+
+        assert_eq!(frames.len(), 4);
+        assert_eq!(
+            frames[1].to_text(),
+            "Row 2: 1 beams, 0 splits\n.S.\n.|.\n.^.\n...\n"
+        );
+        assert_eq!(
+            frames[3].to_text(),
+            "Row 4: 2 beams, 1 splits\n.S.\n.|.\n.^.\n|.|\n"
+        );
+        assert_eq!(
+            frames[3]
+                .cells
+                .get(&Location::new(1, 2))
+                .map(|cell| cell.style),
+            Some(Style::Highlighted)
+        );
+    }
+
+    /// A manifold with its beam start in the top row, and splitters scattered below.
+    fn manifold_input() -> impl Strategy<Value = String> {
+        (1usize..8, 1usize..8).prop_flat_map(|(num_cols, num_rows)| {
+            let row = prop::collection::vec(prop::bool::weighted(0.3), num_cols).prop_map(|row| {
+                row.into_iter()
+                    .map(|is_splitter| if is_splitter { '^' } else { '.' })
+                    .collect::<String>()
+            });
+            (0..num_cols, prop::collection::vec(row, num_rows - 1)).prop_map(
+                move |(start, rows)| {
+                    let mut top = vec!['.'; num_cols];
+                    top[start] = 'S';
+                    let top: String = top.into_iter().collect();
+                    std::iter::once(top)
+                        .chain(rows)
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                },
+            )
+        })
+    }
+
+    /// Follows every path a beam entering row `y` at column `x` could take, one at a time. Beams
+    /// leaving the left side are lost, and so are those leaving the right side above the bottom.
+    fn brute_force_timelines(manifold: &Grid<TachyonManifoldItem>, x: isize, y: usize) -> u64 {
+        let Ok(x) = usize::try_from(x) else {
+            return 0;
+        };
+        if y == manifold.num_rows() {
+            return 1;
+        }
+        let Some(item) = manifold.get(&Location::new(x, y)) else {
+            return 0;
+        };
+        match item {
+            TachyonManifoldItem::Splitter => {
+                brute_force_timelines(manifold, x as isize - 1, y + 1)
+                    + brute_force_timelines(manifold, x as isize + 1, y + 1)
+            }
+            _ => brute_force_timelines(manifold, x as isize, y + 1),
+        }
+    }
+
+    /// The splitters that some beam reaches, found by searching from the beam start.
+    fn reached_splitters(manifold: &Grid<TachyonManifoldItem>, start: usize) -> HashSet<Location> {
+        let mut splitters = HashSet::new();
+        let mut seen = HashSet::new();
+        let mut beams = vec![(start as isize, 1)];
+        while let Some((x, y)) = beams.pop() {
+            let Some(location) = usize::try_from(x).ok().map(|x| Location::new(x, y)) else {
+                continue;
+            };
+            let Some(item) = manifold.get(&location) else {
+                continue;
+            };
+            if !seen.insert(location) {
+                continue;
+            }
+            if *item == TachyonManifoldItem::Splitter {
+                splitters.insert(location);
+                beams.extend([(x - 1, y + 1), (x + 1, y + 1)]);
+            } else {
+                beams.push((x, y + 1));
+            }
+        }
+        splitters
+    }
+
+    proptest! {
+        #[test]
+        fn counts_match_following_each_beam(input in manifold_input()) {
+            let manifold = parse_manifold(&input).unwrap();
+            let start = input.find('S').unwrap() as isize;
+
+            let splitters = reached_splitters(&manifold, start as usize);
+            if splitters.iter().any(|location| location.x == 0) {
+                prop_assert!(count_beam_splits(&manifold).is_err(), "{}", input);
+            } else {
+                prop_assert_eq!(
+                    count_beam_splits(&manifold),
+                    Ok(splitters.len() as u64),
+                    "{}",
+                    input
+                );
+            }
+            prop_assert_eq!(
+                count_timelines(&manifold),
+                Ok(brute_force_timelines(&manifold, start, 1)),
+                "{}",
+                input
+            );
+        }
     }
 }
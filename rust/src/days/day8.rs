@@ -1,202 +1,190 @@
-use std::{
-    collections::{BinaryHeap, HashMap, HashSet},
-    num::ParseFloatError,
-};
-
-use nalgebra::{Point3, point};
-
-use crate::utils::input::Error;
-
-type BoxPoint = Point3<f64>;
-
-struct BoxDistance {
-    id_a: usize,
-    id_b: usize,
-    distance: f64,
-}
-
-impl BoxDistance {
-    fn between(id_a: usize, a: &BoxPoint, id_b: usize, b: &BoxPoint) -> Self {
-        let distance = nalgebra::distance(a, b);
-        Self {
-            id_a,
-            id_b,
-            distance,
-        }
-    }
-}
-
-impl PartialEq for BoxDistance {
-    fn eq(&self, other: &Self) -> bool {
-        self.distance == other.distance
-    }
-}
-impl Eq for BoxDistance {}
-
-impl PartialOrd for BoxDistance {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for BoxDistance {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.distance.total_cmp(&other.distance).reverse()
-    }
-}
-
-fn build_distance_heap(points: &[BoxPoint]) -> BinaryHeap<BoxDistance> {
-    let mut heap = BinaryHeap::with_capacity(points.len() * (points.len() - 1) / 2);
-    for id_a in 0..points.len() {
-        let a = &points[id_a];
-        #[allow(clippy::needless_range_loop)]
-        for id_b in (id_a + 1)..points.len() {
-            let b = &points[id_b];
-            heap.push(BoxDistance::between(id_a, a, id_b, b));
-        }
-    }
-    heap
-}
-
-fn create_closest_n_connections(n: usize, points: &[BoxPoint]) -> Vec<HashSet<usize>> {
-    let mut heap = build_distance_heap(points);
-    let mut circuits_by_point_id: HashMap<usize, usize> =
-        (0..points.len()).map(|p_id| (p_id, p_id)).collect();
-    let mut points_by_circuit_id: HashMap<usize, HashSet<usize>> = circuits_by_point_id
-        .iter()
-        .map(|(&p_id, &c_id)| (c_id, HashSet::from([p_id])))
-        .collect();
-
+use nalgebra::Point3;
+
+use crate::solver::{Answer, Param, ParamOverride, Params, Solver};
+use crate::utils::dsu::DisjointSets;
+use crate::utils::input::{self, Error};
+use crate::utils::spatial::PairsByDistance;
+
+/// Coordinates are parsed as `i32`s, so that squared distances and products of coordinates are
+/// always exact.
+type BoxPoint = Point3<i64>;
+
+/// Connects pairs of points into circuits, closest pairs first, for as long as `keep_connecting`
+/// returns true. It is passed the number of pairs connected so far, including pairs that were
+/// already in the same circuit.
+///
+/// Returns the circuits, and the pairs of point ids that joined two circuits in order.
+fn connect_closest_pairs(
+    points: &[BoxPoint],
+    mut keep_connecting: impl FnMut(usize, &DisjointSets) -> bool,
+) -> (DisjointSets, Vec<(usize, usize)>) {
+    let mut pairs = PairsByDistance::new(points);
+    let mut circuits = DisjointSets::new(points.len());
+    let mut joining_pairs = Vec::new();
     let mut num_connected = 0;
-    while !heap.is_empty() && num_connected < n {
-        let next = heap.pop().unwrap();
-        let c_id_a = circuits_by_point_id[&next.id_a];
-        let c_id_b = circuits_by_point_id[&next.id_b];
+    while keep_connecting(num_connected, &circuits) {
+        let Some(next) = pairs.next() else {
+            break;
+        };
         num_connected += 1;
-
-        if c_id_a == c_id_b {
-            continue;
-        }
-
-        // Swap all B points to be on the A circuit, and drop the B circuit.
-        let b_points = points_by_circuit_id.remove(&c_id_b).unwrap();
-        points_by_circuit_id
-            .get_mut(&c_id_a)
-            .unwrap()
-            .extend(&b_points);
-        for p_id in b_points {
-            circuits_by_point_id.insert(p_id, c_id_a);
+        if circuits.union(next.id_a, next.id_b) {
+            joining_pairs.push((next.id_a, next.id_b));
         }
     }
+    (circuits, joining_pairs)
+}
 
-    let mut circuits: Vec<HashSet<usize>> = points_by_circuit_id.into_values().collect();
-    circuits.sort_unstable_by_key(|c| c.len());
-    circuits.reverse();
-    circuits
+/// Connects the closest `n` pairs, returning the sizes of the circuits from largest to smallest.
+fn create_closest_n_connections(n: usize, points: &[BoxPoint]) -> Vec<usize> {
+    let (circuits, _) = connect_closest_pairs(points, |num_connected, _| num_connected < n);
+    let mut sizes: Vec<usize> = circuits.set_sizes().collect();
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    sizes
 }
 
-/// Connects the points into a single circuit, returning the pairs of points that are connected in order.
+/// Connects the points into a single circuit, returning the pairs of points that are connected in
+/// order.
 fn connect_into_one_circuit(points: &[BoxPoint]) -> Vec<(BoxPoint, BoxPoint)> {
-    let mut heap = build_distance_heap(points);
-    let mut circuits_by_point_id: HashMap<usize, usize> =
-        (0..points.len()).map(|p_id| (p_id, p_id)).collect();
-    let mut points_by_circuit_id: HashMap<usize, HashSet<usize>> = circuits_by_point_id
-        .iter()
-        .map(|(&p_id, &c_id)| (c_id, HashSet::from([p_id])))
-        .collect();
-    let mut connected_points = Vec::new();
-
-    while !heap.is_empty() && points_by_circuit_id.len() > 1 {
-        let next = heap.pop().unwrap();
-        let c_id_a = circuits_by_point_id[&next.id_a];
-        let c_id_b = circuits_by_point_id[&next.id_b];
-
-        if c_id_a == c_id_b {
-            continue;
-        }
-
-        connected_points.push((
-            *points.get(next.id_a).unwrap(),
-            *points.get(next.id_b).unwrap(),
-        ));
-
-        // Swap all B points to be on the A circuit, and drop the B circuit.
-        let b_points = points_by_circuit_id.remove(&c_id_b).unwrap();
-        points_by_circuit_id
-            .get_mut(&c_id_a)
-            .unwrap()
-            .extend(&b_points);
-        for p_id in b_points {
-            circuits_by_point_id.insert(p_id, c_id_a);
-        }
-    }
-
-    connected_points
+    let (_, joining_pairs) = connect_closest_pairs(points, |_, circuits| circuits.num_sets() > 1);
+    joining_pairs
+        .into_iter()
+        .map(|(id_a, id_b)| (points[id_a], points[id_b]))
+        .collect()
 }
 
 pub fn parse_points(input: &str) -> Result<Vec<BoxPoint>, Error> {
-    input
-        .lines()
-        .map(|p_str| {
-            let parse_result: Result<Vec<f64>, ParseFloatError> =
-                p_str.split(',').map(|n_str| n_str.trim().parse()).collect();
-            let numbers = parse_result
-                .map_err(|e| Error::ParseError(format!("error parsing number: {:?}", e)))?;
-            if numbers.len() != 3 {
-                return Err(Error::ParseError(format!(
-                    "points must have three numbers, parsed {} from {}",
-                    numbers.len(),
-                    p_str
-                )));
-            }
-            Ok(point![numbers[0], numbers[1], numbers[2]])
-        })
-        .collect()
+    input::parse_lines(input, |line| {
+        let coordinates: [i32; 3] = input::parse_tuple(line, ',')?;
+        Ok(BoxPoint::from(coordinates.map(i64::from)))
+    })
 }
 
+/// Multiplies the sizes of the `n` largest circuits after connecting the closest `m` pairs.
+///
+/// Fails if there are fewer than `n` circuits, or if the product overflows.
 pub fn multiply_n_largest_circuits_after_m_connections(
     n: usize,
     m: usize,
     points: &[BoxPoint],
-) -> u64 {
-    let circuits = create_closest_n_connections(m, points);
-    log::info!("Created {} circuits", circuits.len());
-    let mut result = 1;
-    for i in 0..n {
-        if let Some(c) = circuits.get(i) {
-            log::info!("Multiplying circuit sized: {}", c.len());
-            result *= c.len() as u64;
-        } else {
-            break;
-        }
+) -> Result<u64, Error> {
+    let sizes = create_closest_n_connections(m, points);
+    log::info!("Created {} circuits", sizes.len());
+    if n > sizes.len() {
+        return Err(Error::LogicError(format!(
+            "cannot multiply the {} largest circuits, as there are only {}",
+            n,
+            sizes.len()
+        )));
     }
-    result
+    sizes[..n].iter().try_fold(1u64, |result, &size| {
+        log::info!("Multiplying circuit sized: {}", size);
+        result.checked_mul(size as u64).ok_or_else(|| {
+            Error::LogicError(format!(
+                "product of the {} largest circuit sizes overflows",
+                n
+            ))
+        })
+    })
 }
 
-pub fn part2(points: &[BoxPoint]) -> f64 {
+/// Multiplies the x coordinates of the last pair connected to form a single circuit.
+pub fn part2(points: &[BoxPoint]) -> Result<i64, Error> {
     let pairs = connect_into_one_circuit(points);
     log::info!("Connected {} pairs", pairs.len());
 
-    pairs.last().map(|(a, b)| a.x * b.x).unwrap_or(0.)
+    // Both coordinates fit in an i32, so their product fits in an i64.
+    pairs
+        .last()
+        .map(|(a, b)| a.x * b.x)
+        .ok_or_else(|| Error::LogicError("need at least two junction boxes".to_string()))
+}
+
+pub struct Day8;
+
+impl Solver for Day8 {
+    type Parsed = Vec<BoxPoint>;
+
+    const PARAMS: &'static [Param] = &[
+        Param {
+            name: "connections",
+            description: "closest pairs to connect",
+            default: 1000,
+            overrides: &[ParamOverride {
+                part: None,
+                dataset: Some("small"),
+                value: 10,
+            }],
+        },
+        Param {
+            name: "circuits",
+            description: "largest circuits to multiply the sizes of",
+            default: 3,
+            overrides: &[],
+        },
+    ];
+
+    fn parse(&self, input: &str) -> Result<Vec<BoxPoint>, Error> {
+        parse_points(input)
+    }
+
+    fn part1(&self, points: &Vec<BoxPoint>, params: &Params) -> Result<Answer, Error> {
+        multiply_n_largest_circuits_after_m_connections(
+            params.get("circuits")?,
+            params.get("connections")?,
+            points,
+        )
+        .map(Answer::from)
+    }
+
+    fn part2(&self, points: &Vec<BoxPoint>, _params: &Params) -> Result<Answer, Error> {
+        part2(points).map(Answer::from)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use nalgebra::point;
+    use proptest::prelude::*;
+
     use super::*;
+    use crate::utils::input::error_position;
+
+    /// The example from the puzzle description.
+    const EXAMPLE: &str = "162,817,812
+57,618,57
+906,360,560
+592,479,940
+352,342,300
+466,668,158
+542,29,236
+431,825,988
+739,650,466
+52,470,668
+216,146,977
+819,987,18
+117,168,530
+805,96,715
+346,949,466
+970,615,88
+941,993,340
+862,61,35
+984,92,344
+425,690,690";
+    use crate::utils::spatial::{PointPair, squared_distance};
 
     #[test]
     fn test_create_closest_n_connections() {
-        let circuits = create_closest_n_connections(
+        let sizes = create_closest_n_connections(
             2,
             &[
-                BoxPoint::new(1., 1., 1.),
-                BoxPoint::new(5., 5., 5.),
-                BoxPoint::new(2., 2., 2.),
-                BoxPoint::new(2., 1., 1.),
+                BoxPoint::new(1, 1, 1),
+                BoxPoint::new(5, 5, 5),
+                BoxPoint::new(2, 2, 2),
+                BoxPoint::new(2, 1, 1),
             ],
         );
 
-        assert_eq!(circuits, vec!(HashSet::from([0, 2, 3]), HashSet::from([1])));
+        assert_eq!(sizes, vec![3, 1]);
     }
 
     #[test]
@@ -209,104 +197,147 @@ mod tests {
         assert!(result.is_ok(), "{:?}", result.err());
         assert_eq!(
             result.unwrap(),
-            vec![point![1., 32., 3.], point![-5., 9., 0.], point![3., 2., 2.]]
+            vec![point![1, 32, 3], point![-5, 9, 0], point![3, 2, 2]]
         );
     }
 
+    #[test]
+    fn test_parse_points_error_position() {
+        assert_eq!(
+            error_position(parse_points("1,32,3\n-5,9x,0")),
+            Some((2, 4))
+        );
+
+        let err = parse_points("1,32,3\n-5,9").unwrap_err();
+        assert_eq!(err.position().map(|p| p.span.clone()), Some(7..11));
+    }
+
     #[test]
     fn test_multiply_n_largest_circuits_after_m_connections() {
-        let points = parse_points(
-            "162,817,812
-57,618,57
-906,360,560
-592,479,940
-352,342,300
-466,668,158
-542,29,236
-431,825,988
-739,650,466
-52,470,668
-216,146,977
-819,987,18
-117,168,530
-805,96,715
-346,949,466
-970,615,88
-941,993,340
-862,61,35
-984,92,344
-425,690,690",
-        )
-        .unwrap();
+        let points = parse_points(EXAMPLE).unwrap();
         let total = multiply_n_largest_circuits_after_m_connections(3, 10, &points);
 
-        assert_eq!(total, 40);
+        assert_eq!(total, Ok(40));
+        assert!(matches!(
+            multiply_n_largest_circuits_after_m_connections(40, 10, &points),
+            Err(Error::LogicError(_))
+        ));
+    }
+
+    #[test]
+    fn test_multiply_n_largest_circuits_overflow() {
+        // 100 far apart pairs of neighbouring points, so 100 circuits of 2 once connected.
+        let points: Vec<BoxPoint> = (0..100)
+            .flat_map(|i| [point![1000 * i, 0, 0], point![1000 * i + 1, 0, 0]])
+            .collect();
+
+        assert_eq!(
+            multiply_n_largest_circuits_after_m_connections(63, 100, &points),
+            Ok(1 << 63)
+        );
+        assert!(matches!(
+            multiply_n_largest_circuits_after_m_connections(64, 100, &points),
+            Err(Error::LogicError(_))
+        ));
     }
 
     #[test]
     fn test_connect_into_one_circuit() {
-        let points = parse_points(
-            "162,817,812
-57,618,57
-906,360,560
-592,479,940
-352,342,300
-466,668,158
-542,29,236
-431,825,988
-739,650,466
-52,470,668
-216,146,977
-819,987,18
-117,168,530
-805,96,715
-346,949,466
-970,615,88
-941,993,340
-862,61,35
-984,92,344
-425,690,690",
-        )
-        .unwrap();
+        let points = parse_points(EXAMPLE).unwrap();
         let connected_pairs = connect_into_one_circuit(&points);
 
         let (a, b) = *connected_pairs.last().unwrap();
 
-        assert!(a.x == 216. || a.x == 117.);
-        if a.x == 216. {
-            assert_eq!(b.x, 117.);
-        } else {
-            assert_eq!(b.x, 216.);
-        }
+        // Ties are broken by point id, so the order within the pair is fixed.
+        assert_eq!((a.x, b.x), (216, 117));
     }
 
     #[test]
     fn test_part2() {
-        let points = parse_points(
-            "162,817,812
-57,618,57
-906,360,560
-592,479,940
-352,342,300
-466,668,158
-542,29,236
-431,825,988
-739,650,466
-52,470,668
-216,146,977
-819,987,18
-117,168,530
-805,96,715
-346,949,466
-970,615,88
-941,993,340
-862,61,35
-984,92,344
-425,690,690",
-        )
-        .unwrap();
+        let points = parse_points(EXAMPLE).unwrap();
         let total = part2(&points);
 
-        assert_eq!(total, 25272.);
+        assert_eq!(total, Ok(25272));
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let points = parse_points("").unwrap();
+        assert!(matches!(
+            multiply_n_largest_circuits_after_m_connections(3, 10, &points),
+            Err(Error::LogicError(_))
+        ));
+        assert_eq!(
+            multiply_n_largest_circuits_after_m_connections(0, 10, &points),
+            Ok(1)
+        );
+        let err = Error::LogicError("need at least two junction boxes".to_string());
+        assert_eq!(part2(&points), Err(err.clone()));
+        assert_eq!(part2(&[point![1, 2, 3]]), Err(err));
+    }
+
+    #[test]
+    fn extreme_coordinates_are_exact() {
+        assert!(parse_points("2147483648,0,0").is_err());
+
+        let points = parse_points("-2147483648,0,0\n2147483647,0,0\n2147483647,1,0").unwrap();
+        assert_eq!(part2(&points), Ok(-2147483648 * 2147483647));
+    }
+
+    fn small_points() -> impl Strategy<Value = Vec<BoxPoint>> {
+        prop::collection::vec((-20i64..20, -20i64..20, -20i64..20), 1..12).prop_map(|points| {
+            points
+                .into_iter()
+                .map(|(x, y, z)| point![x, y, z])
+                .collect()
+        })
+    }
+
+    /// The circuits after connecting the closest `m` pairs, labelling each point with its circuit
+    /// and relabelling a whole circuit whenever it is joined to another.
+    fn naive_circuits(points: &[BoxPoint], m: usize) -> Vec<Vec<usize>> {
+        let mut pairs = Vec::new();
+        for id_b in 0..points.len() {
+            for id_a in 0..id_b {
+                let squared_distance = squared_distance(&points[id_a], &points[id_b]);
+                pairs.push(PointPair {
+                    squared_distance,
+                    id_a,
+                    id_b,
+                });
+            }
+        }
+        pairs.sort();
+
+        let mut labels: Vec<usize> = (0..points.len()).collect();
+        for pair in pairs.iter().take(m) {
+            let (from, to) = (labels[pair.id_b], labels[pair.id_a]);
+            for label in labels.iter_mut().filter(|label| **label == from) {
+                *label = to;
+            }
+        }
+        let mut circuits: Vec<(usize, Vec<usize>)> = Vec::new();
+        for (id, &label) in labels.iter().enumerate() {
+            match circuits.iter_mut().find(|(l, _)| *l == label) {
+                Some((_, circuit)) => circuit.push(id),
+                None => circuits.push((label, vec![id])),
+            }
+        }
+        circuits.into_iter().map(|(_, circuit)| circuit).collect()
+    }
+
+    proptest! {
+        #[test]
+        fn circuits_match_naive_labelling(points in small_points(), m in 0usize..70) {
+            let (mut circuits, joining) = connect_closest_pairs(&points, |k, _| k < m);
+
+            prop_assert_eq!(circuits.num_sets(), points.len() - joining.len());
+            prop_assert_eq!(circuits.sets(), naive_circuits(&points, m));
+        }
+
+        #[test]
+        fn one_circuit_takes_one_less_pair_than_points(points in small_points()) {
+            prop_assert_eq!(connect_into_one_circuit(&points).len(), points.len() - 1);
+        }
     }
 }
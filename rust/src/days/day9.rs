@@ -3,10 +3,15 @@ use std::fmt::Debug;
 
 use nalgebra::{Point2, point};
 
-use crate::utils::input::Error;
+use crate::solver::{Answer, Params, Part, Solver};
+use crate::utils::input::{self, Error};
+use crate::visualize::{Visualization, VisualizeOptions};
 
 type TilePoint = Point2<usize>;
 
+/// The largest coordinate accepted, so that rectangle areas always fit in an `i64`.
+const MAX_COORDINATE: usize = i32::MAX as usize;
+
 fn rectangle_area(a: &TilePoint, b: &TilePoint) -> i64 {
     let width = a.x.max(b.x) - b.x.min(a.x);
     let height = a.y.max(b.y) - b.y.min(a.y);
@@ -14,16 +19,17 @@ fn rectangle_area(a: &TilePoint, b: &TilePoint) -> i64 {
     ((width as i64) + 1) * ((height as i64) + 1)
 }
 
-fn largest_rectangle_with_corners(points: &[TilePoint]) -> [TilePoint; 2] {
+/// The largest rectangle with corners at two of `points`, or None if there are fewer than two.
+fn largest_rectangle_with_corners(points: &[TilePoint]) -> Option<[TilePoint; 2]> {
     let mut largest_area = 0;
-    let mut largest_rectangle = [point![0, 0], point![0, 0]];
+    let mut largest_rectangle = None;
 
     for i in 0..points.len() {
         let a = points[i];
         for b in points.iter().skip(i + 1) {
             let area = rectangle_area(&a, b);
             if area > largest_area {
-                largest_rectangle = [a, *b];
+                largest_rectangle = Some([a, *b]);
                 largest_area = area;
             }
         }
@@ -95,6 +101,16 @@ impl Direction {
         )
     }
 
+    /// The unit step in this direction, with y growing downwards as in the input.
+    fn step(&self) -> (f64, f64) {
+        match self {
+            Self::Left => (-1.0, 0.0),
+            Self::Right => (1.0, 0.0),
+            Self::Up => (0.0, -1.0),
+            Self::Down => (0.0, 1.0),
+        }
+    }
+
     fn rotate(&self, rotation: Rotation) -> Self {
         match (*self, rotation) {
             (Self::Left, Rotation::Clockwise) => Self::Up,
@@ -128,7 +144,7 @@ impl SquarePolygon {
         let lines: Vec<_> = points
             .last()
             .into_iter()
-            .chain(points.iter().take(points.len() - 1))
+            .chain(points.iter().take(points.len().saturating_sub(1)))
             .zip(points.iter())
             .map(|(&a, &b)| (a, b))
             .collect();
@@ -254,27 +270,46 @@ impl SquarePolygon {
     }
 }
 
-fn largest_green_red_rectangle_with_corners(points: &[TilePoint]) -> [TilePoint; 2] {
+fn largest_green_red_rectangle_with_corners(points: &[TilePoint]) -> Option<[TilePoint; 2]> {
     log::info!("Constructing polygon");
     let shape = SquarePolygon::from_connected_points(points);
+    largest_rectangle_inside(&shape, points, |_| {})
+}
 
+/// Finds the largest rectangle inside `shape` with corners at two of `points`.
+///
+/// Only rectangles larger than the best so far are checked, so `on_rejected_improvement` is
+/// passed just those that would have improved on it but were not inside, not every rectangle
+/// that is outside.
+///
+/// Returns None if no rectangle is inside, e.g. because there are fewer than two points.
+fn largest_rectangle_inside(
+    shape: &SquarePolygon,
+    points: &[TilePoint],
+    mut on_rejected_improvement: impl FnMut([TilePoint; 2]),
+) -> Option<[TilePoint; 2]> {
     log::info!("Finding largest valid rectangle");
     let mut largest_area = 0;
-    let mut largest_rectangle = [point![0, 0], point![0, 0]];
+    let mut largest_rectangle = None;
 
     for i in 0..points.len() {
         let a = points[i];
         for b in points.iter().skip(i + 1) {
             let area = rectangle_area(&a, b);
-            if area > largest_area && shape.is_rectangle_fully_inside(&a, b) {
+            if area <= largest_area {
+                continue;
+            }
+            if shape.is_rectangle_fully_inside(&a, b) {
                 log::debug!(
                     "Found largest rectangle so far (area: {}) at ({}, {})",
                     area,
                     &a,
                     b
                 );
-                largest_rectangle = [a, *b];
+                largest_rectangle = Some([a, *b]);
                 largest_area = area;
+            } else {
+                on_rejected_improvement([a, *b]);
             }
         }
     }
@@ -282,45 +317,263 @@ fn largest_green_red_rectangle_with_corners(points: &[TilePoint]) -> [TilePoint;
     largest_rectangle
 }
 
-fn parse_point(input: &str) -> Result<TilePoint, Error> {
-    let numbers: Vec<&str> = input.split(',').collect();
-    if numbers.len() != 2 {
-        return Err(Error::ParseError(format!(
-            "points must have 2 numbers, found {} in {}",
-            numbers.len(),
-            input
-        )));
-    }
-    let x = numbers[0];
-    let y = numbers[1];
-    Ok(point![
-        x.parse::<usize>()
-            .map_err(|err| Error::ParseError(format!("could not parse number {}; {:?}", x, err)))?,
-        y.parse::<usize>()
-            .map_err(|err| Error::ParseError(format!("could not parse number {}; {:?}", y, err)))?
-    ])
+/// Width of the drawing in pixels; the height follows the tiles' aspect ratio.
+const SVG_WIDTH: f64 = 800.0;
+
+/// The SVG rectangle covering every tile between corners `a` and `b`.
+fn svg_rect(class: &str, a: &TilePoint, b: &TilePoint) -> String {
+    format!(
+        r#"<rect class="{}" x="{}" y="{}" width="{}" height="{}"/>"#,
+        class,
+        a.x.min(b.x),
+        a.y.min(b.y),
+        a.x.abs_diff(b.x) + 1,
+        a.y.abs_diff(b.y) + 1
+    )
 }
 
+/// Draws the polygon through the red tiles as an SVG.
+///
+/// The polygon's edges run through the middle of each tile, with a tick on each edge pointing to
+/// the side the polygon thinks is filled. Red tiles are candidate corners, and `winner`, if there
+/// is one, is drawn over any `rejected` rectangles.
+fn polygon_svg(
+    shape: &SquarePolygon,
+    points: &[TilePoint],
+    winner: Option<&[TilePoint; 2]>,
+    rejected: &[[TilePoint; 2]],
+) -> String {
+    let min_x = points.iter().map(|p| p.x).min().unwrap_or(0) as f64;
+    let min_y = points.iter().map(|p| p.y).min().unwrap_or(0) as f64;
+    let max_x = points.iter().map(|p| p.x).max().unwrap_or(0) as f64 + 1.0;
+    let max_y = points.iter().map(|p| p.y).max().unwrap_or(0) as f64 + 1.0;
+    let extent = (max_x - min_x).max(max_y - min_y);
+    // Sizes of markers, in tiles, so that they look the same at any scale.
+    let unit = extent / 200.0;
+    let margin = unit * 10.0;
+    let view_width = max_x - min_x + 2.0 * margin;
+    let view_height = max_y - min_y + 2.0 * margin;
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{:.0}">
+<style>
+  * {{ vector-effect: non-scaling-stroke; stroke-width: 1px; }}
+  .polygon {{ fill: #4caf50; fill-opacity: 0.2; stroke: #2e7d32; }}
+  .fill-side {{ stroke: #2e7d32; }}
+  .rejected {{ fill: none; stroke: #ff9800; stroke-opacity: 0.4; }}
+  .winner {{ fill: #1976d2; fill-opacity: 0.3; stroke: #0d47a1; stroke-width: 2px; }}
+  .corner {{ fill: #d32f2f; }}
+</style>
+"#,
+        min_x - margin,
+        min_y - margin,
+        view_width,
+        view_height,
+        SVG_WIDTH,
+        SVG_WIDTH * view_height / view_width
+    );
+
+    let center = |p: &TilePoint| (p.x as f64 + 0.5, p.y as f64 + 0.5);
+    let corners: Vec<String> = points
+        .iter()
+        .map(|p| {
+            let (x, y) = center(p);
+            format!("{},{}", x, y)
+        })
+        .collect();
+    svg += &format!(
+        "<polygon class=\"polygon\" points=\"{}\"/>\n",
+        corners.join(" ")
+    );
+
+    svg += "<g class=\"fill-side\">\n";
+    for (line, direction) in shape.lines.iter().zip(shape.line_directions.iter()) {
+        let (start_x, start_y) = center(&line.0);
+        let (end_x, end_y) = center(&line.1);
+        let (mid_x, mid_y) = ((start_x + end_x) / 2.0, (start_y + end_y) / 2.0);
+        let (dx, dy) = direction.rotate(shape.fill_rotation).step();
+        svg += &format!(
+            "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n",
+            mid_x,
+            mid_y,
+            mid_x + dx * unit * 4.0,
+            mid_y + dy * unit * 4.0
+        );
+    }
+    svg += "</g>\n";
+
+    if !rejected.is_empty() {
+        svg += "<g>\n";
+        for [a, b] in rejected {
+            svg += &format!("  {}\n", svg_rect("rejected", a, b));
+        }
+        svg += "</g>\n";
+    }
+
+    if let Some([a, b]) = winner {
+        svg += &svg_rect("winner", a, b);
+        svg += "\n";
+    }
+
+    svg += "<g class=\"corner\">\n";
+    for p in points {
+        let (x, y) = center(p);
+        svg += &format!(
+            "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\"/>\n",
+            x,
+            y,
+            unit.max(0.2)
+        );
+    }
+    svg += "</g>\n</svg>\n";
+    svg
+}
+
+/// Draws the polygon with the winning rectangle of the given part.
+///
+/// Part 1 ignores the polygon, so it never rejects a rectangle.
+fn visualize_rectangles(points: &[TilePoint], part: Part, show_rejected: bool) -> String {
+    let shape = SquarePolygon::from_connected_points(points);
+    let mut rejected = Vec::new();
+    let winner = match part {
+        Part::One => largest_rectangle_with_corners(points),
+        Part::Two => largest_rectangle_inside(&shape, points, |rectangle| {
+            if show_rejected {
+                rejected.push(rectangle);
+            }
+        }),
+    };
+    polygon_svg(&shape, points, winner.as_ref(), &rejected)
+}
+
+fn parse_coordinate(text: &str) -> Result<usize, Error> {
+    let value = input::parse_number(text)?;
+    if value > MAX_COORDINATE {
+        return Err(Error::parse_at(
+            text,
+            text,
+            format!("coordinates must be at most {}", MAX_COORDINATE),
+        ));
+    }
+    Ok(value)
+}
+
+/// Parses the red tiles, each of which must share a row or column with the one before it, and
+/// the last with the first, so that they form a loop of straight lines.
 fn parse_points(input: &str) -> Result<Vec<TilePoint>, Error> {
-    input.lines().map(parse_point).collect()
+    let points = input::parse_lines(input, |line| {
+        let [x, y] = input::parse_tuple_with(line, ',', parse_coordinate)?;
+        Ok(point![x, y])
+    })?;
+    let lines: Vec<&str> = input.lines().collect();
+    for (i, point) in points.iter().enumerate() {
+        let previous = points[(i + points.len() - 1) % points.len()];
+        if point.x != previous.x && point.y != previous.y {
+            return Err(Error::parse_at(
+                input,
+                lines[i],
+                format!(
+                    "tile must share a row or column with the tile before it, {},{}",
+                    previous.x, previous.y
+                ),
+            ));
+        }
+    }
+    Ok(points)
+}
+
+/// The area of the rectangle with corners `rectangle`, or an error if too few tiles gave none.
+fn rectangle_answer(rectangle: Option<[TilePoint; 2]>) -> Result<i64, Error> {
+    rectangle
+        .map(|[a, b]| rectangle_area(&a, &b))
+        .ok_or_else(|| Error::LogicError("need at least two red tiles".to_string()))
 }
 
-pub fn part1(input: &str) -> Result<i64, Error> {
-    let points = parse_points(input)?;
-    let largest_rectangle = largest_rectangle_with_corners(&points);
-    Ok(rectangle_area(&largest_rectangle[0], &largest_rectangle[1]))
+/// The area of the largest rectangle with red corners.
+pub fn part1(points: &[TilePoint]) -> Result<i64, Error> {
+    rectangle_answer(largest_rectangle_with_corners(points))
 }
 
-pub fn part2(input: &str) -> Result<i64, Error> {
-    log::info!("Parsing points");
-    let points = parse_points(input)?;
-    let largest_rectangle = largest_green_red_rectangle_with_corners(&points);
-    Ok(rectangle_area(&largest_rectangle[0], &largest_rectangle[1]))
+/// The area of the largest red and green rectangle with red corners.
+pub fn part2(points: &[TilePoint]) -> Result<i64, Error> {
+    rectangle_answer(largest_green_red_rectangle_with_corners(points))
+}
+
+pub struct Day9;
+
+impl Solver for Day9 {
+    type Parsed = Vec<TilePoint>;
+
+    fn parse(&self, input: &str) -> Result<Vec<TilePoint>, Error> {
+        parse_points(input)
+    }
+
+    fn part1(&self, points: &Vec<TilePoint>, _params: &Params) -> Result<Answer, Error> {
+        part1(points).map(Answer::from)
+    }
+
+    fn part2(&self, points: &Vec<TilePoint>, _params: &Params) -> Result<Answer, Error> {
+        part2(points).map(Answer::from)
+    }
+
+    fn visualize(
+        &self,
+        points: &Vec<TilePoint>,
+        part: Part,
+        options: &VisualizeOptions,
+    ) -> Option<Visualization> {
+        Some(Visualization::Svg(visualize_rectangles(
+            points,
+            part,
+            options.show_rejected,
+        )))
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
+    use crate::utils::input::error_position;
+
+    #[test]
+    fn parse_points_rejects_huge_coordinates() {
+        assert!(parse_points("7,2147483647").is_ok());
+        assert!(parse_points("7,2147483648").is_err());
+    }
+
+    #[test]
+    fn empty_input() {
+        let points = parse_points("").unwrap();
+        let err = Error::LogicError("need at least two red tiles".to_string());
+        assert_eq!(part1(&points), Err(err.clone()));
+        assert_eq!(part2(&points), Err(err.clone()));
+        assert_eq!(part1(&[point![3, 4]]), Err(err.clone()));
+        assert_eq!(part2(&[point![3, 4]]), Err(err));
+    }
+
+    #[test]
+    fn parse_points_error_position() {
+        assert_eq!(
+            error_position(parse_points("7,1\n11,x\n11,7")),
+            Some((2, 4))
+        );
+    }
+
+    #[test]
+    fn parse_points_must_turn_in_straight_lines() {
+        assert!(parse_points("7,1\n11,1\n11,7\n7,7").is_ok());
+        assert_eq!(
+            error_position(parse_points("7,1\n11,1\n12,7\n7,7")),
+            Some((3, 1))
+        );
+        // The last tile joins back up with the first.
+        assert_eq!(
+            error_position(parse_points("7,1\n11,1\n11,7\n8,7")),
+            Some((1, 1))
+        );
+    }
 
     #[test]
     fn test_largest_rectangle_with_corners() {
@@ -334,7 +587,7 @@ mod tests {
             point!(2, 3),
             point!(7, 3),
         ];
-        let [a, b] = largest_rectangle_with_corners(&points);
+        let [a, b] = largest_rectangle_with_corners(&points).unwrap();
 
         assert_eq!(rectangle_area(&a, &b), 50);
     }
@@ -456,8 +709,164 @@ mod tests {
             point!(2, 3),
             point!(7, 3),
         ];
-        let [a, b] = largest_green_red_rectangle_with_corners(&points);
+        let [a, b] = largest_green_red_rectangle_with_corners(&points).unwrap();
 
         assert_eq!(rectangle_area(&a, &b), 24);
     }
+
+    fn example_points() -> Vec<TilePoint> {
+        parse_points("7,1\n11,1\n11,7\n9,7\n9,5\n2,5\n2,3\n7,3").unwrap()
+    }
+
+    #[test]
+    fn svg_draws_polygon_and_winner() {
+        let svg = visualize_rectangles(&example_points(), Part::Two, false);
+
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(r#"<polygon class="polygon" points="7.5,1.5 11.5,1.5 "#));
+        assert_eq!(svg.matches("<line ").count(), 8);
+        assert_eq!(svg.matches("<circle ").count(), 8);
+        // The 24 tile rectangle from (9, 5) to (2, 3).
+        assert!(svg.contains(r#"<rect class="winner" x="2" y="3" width="8" height="3"/>"#));
+        assert!(!svg.contains("rejected\""));
+    }
+
+    #[test]
+    fn svg_fill_side_points_inside() {
+        let svg = visualize_rectangles(&example_points(), Part::Two, false);
+        let attribute = |line: &str, name: &str| -> f64 {
+            let value = line.split(&format!(" {}=\"", name)).nth(1).unwrap();
+            value[..value.find('"').unwrap()].parse().unwrap()
+        };
+
+        // The top edge runs right from (7, 1) to (11, 1), so the inside is below it.
+        let tick = svg
+            .lines()
+            .find(|line| line.contains(r#"<line x1="9.5" y1="1.5""#))
+            .unwrap();
+        assert_eq!(attribute(tick, "x2"), 9.5);
+        assert!(attribute(tick, "y2") > 1.5);
+    }
+
+    #[test]
+    fn svg_shows_rejected_candidates() {
+        let svg = visualize_rectangles(&example_points(), Part::Two, true);
+        assert!(svg.matches(r#"<rect class="rejected""#).count() > 0);
+
+        let svg = visualize_rectangles(&example_points(), Part::One, true);
+        assert_eq!(svg.matches(r#"<rect class="rejected""#).count(), 0);
+        assert!(svg.contains(r#"<rect class="winner" x="2" y="1" width="10" height="5"/>"#));
+    }
+
+    #[test]
+    fn svg_of_empty_input() {
+        let svg = visualize_rectangles(&[], Part::Two, true);
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(!svg.contains("winner\""));
+    }
+
+    /// A shape made of rows of cells, where each row's cells overlap the row above's, so that its
+    /// outline is a simple polygon. Each row is its first and last cell.
+    fn overlapping_rows() -> impl Strategy<Value = Vec<(usize, usize)>> {
+        prop::collection::vec((0usize..10, 0usize..6), 1..10).prop_map(|rows| {
+            let mut shape: Vec<(usize, usize)> = Vec::with_capacity(rows.len());
+            for (start, len) in rows {
+                let (mut first, mut last) = (start, start + len);
+                if let Some(&(above_first, above_last)) = shape.last() {
+                    last = last.max(above_first);
+                    first = first.min(above_last);
+                }
+                shape.push((first, last));
+            }
+            shape
+        })
+    }
+
+    /// The corners of the outline of the rows, in order around it. Cell `(x, y)` covers the tiles
+    /// from `(2x, 2y)` to `(2x + 2, 2y + 2)`, so that lines of the outline never run along
+    /// neighbouring tiles, and a rectangle whose tiles are all inside really is inside.
+    fn outline(rows: &[(usize, usize)]) -> Vec<TilePoint> {
+        let mut points = vec![point![2 * rows[0].0, 0]];
+        for (y, &(_, last)) in rows.iter().enumerate() {
+            points.extend([point![2 * last + 2, 2 * y], point![2 * last + 2, 2 * y + 2]]);
+        }
+        for (y, &(first, _)) in rows.iter().enumerate().rev() {
+            points.extend([point![2 * first, 2 * y + 2], point![2 * first, 2 * y]]);
+        }
+        // Only keep the corners: drop repeated points, and points in the middle of a line.
+        loop {
+            let n = points.len();
+            let Some(i) = (0..n).find(|&i| {
+                let (before, p, after) = (points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+                p == before
+                    || (before.x == p.x && p.x == after.x)
+                    || (before.y == p.y && p.y == after.y)
+            }) else {
+                return points;
+            };
+            points.remove(i);
+        }
+    }
+
+    /// Whether each tile is red or green, i.e. on or inside the outline: a tile is covered by
+    /// some cell.
+    fn rasterize(rows: &[(usize, usize)]) -> Vec<Vec<bool>> {
+        let width = rows
+            .iter()
+            .map(|&(_, last)| 2 * last + 3)
+            .max()
+            .unwrap_or(0);
+        let mut tiles = vec![vec![false; width]; 2 * rows.len() + 1];
+        for (y, &(first, last)) in rows.iter().enumerate() {
+            for row in &mut tiles[(2 * y)..=(2 * y + 2)] {
+                row[(2 * first)..=(2 * last + 2)].fill(true);
+            }
+        }
+        tiles
+    }
+
+    fn is_inside(tiles: &[Vec<bool>], a: &TilePoint, b: &TilePoint) -> bool {
+        (a.y.min(b.y)..=a.y.max(b.y)).all(|y| (a.x.min(b.x)..=a.x.max(b.x)).all(|x| tiles[y][x]))
+    }
+
+    proptest! {
+        #[test]
+        fn outline_is_a_red_green_polygon(rows in overlapping_rows()) {
+            let points = outline(&rows);
+            prop_assert!(points.len() >= 4);
+            for (i, a) in points.iter().enumerate() {
+                let b = points[(i + 1) % points.len()];
+                prop_assert!((a.x == b.x) != (a.y == b.y), "{:?}", points);
+            }
+        }
+
+        #[test]
+        fn accepted_rectangles_are_inside(rows in overlapping_rows()) {
+            let points = outline(&rows);
+            let tiles = rasterize(&rows);
+            let shape = SquarePolygon::from_connected_points(&points);
+            for (i, a) in points.iter().enumerate() {
+                for b in &points[(i + 1)..] {
+                    if shape.is_rectangle_fully_inside(a, b) {
+                        prop_assert!(is_inside(&tiles, a, b), "{} to {} in {:?}", a, b, points);
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn largest_green_red_rectangle_matches_raster(rows in overlapping_rows()) {
+            let points = outline(&rows);
+            let tiles = rasterize(&rows);
+            let mut largest_area = 0;
+            for (i, a) in points.iter().enumerate() {
+                for b in &points[(i + 1)..] {
+                    if is_inside(&tiles, a, b) {
+                        largest_area = largest_area.max(rectangle_area(a, b));
+                    }
+                }
+            }
+            prop_assert_eq!(part2(&points), Ok(largest_area), "{:?}", points);
+        }
+    }
 }
@@ -1,10 +1,32 @@
 // Advent of Code 2025 day solutions
-// Add day modules as they are implemented
-
-pub mod day3;
-pub mod day4;
-pub mod day5;
-pub mod day6;
-pub mod day7;
-pub mod day8;
-pub mod day9;
+// Register each day's module and solver in the `days!` list below
+
+use crate::solver::DynSolver;
+
+/// Declares each day's module and adds its solver to [`SOLVERS`].
+macro_rules! days {
+    ($($day:literal => $module:ident::$solver:ident),* $(,)?) => {
+        $(pub mod $module;)*
+
+        /// Every implemented day, in order, with its solver.
+        pub static SOLVERS: &[(u8, &dyn DynSolver)] = &[$(($day, &$module::$solver)),*];
+    };
+}
+
+days! {
+    3 => day3::Day3,
+    4 => day4::Day4,
+    5 => day5::Day5,
+    6 => day6::Day6,
+    7 => day7::Day7,
+    8 => day8::Day8,
+    9 => day9::Day9,
+}
+
+/// Looks up the solver for the given day.
+pub fn solver_for(day: u8) -> Option<&'static dyn DynSolver> {
+    SOLVERS
+        .iter()
+        .find(|(solver_day, _)| *solver_day == day)
+        .map(|(_, solver)| *solver)
+}
@@ -0,0 +1,130 @@
+// Errors reported by the command-line tool, each with its own process exit code
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use thiserror::Error as ThisError;
+
+use crate::utils::input;
+
+/// Everything that can stop a command from succeeding.
+///
+/// Each kind of failure exits with a different code, so scripts can tell them apart:
+///
+/// - 1, `Failed`: everything ran, but an answer was wrong or refused.
+/// - 2, `Usage`: invalid arguments or missing settings.
+/// - 3, `Io`: a file or stdin could not be read or written.
+/// - 4, `Parse` or `InvalidFile`: malformed puzzle input or data files.
+/// - 5, `Solve`: the input was valid, but could not be solved.
+/// - 6, `Network`: a request to the website failed.
+#[derive(Debug, Clone, PartialEq, ThisError)]
+pub enum AppError {
+    /// Arguments that clap accepted but which make no sense, e.g. a day that is not implemented.
+    #[error("{0}")]
+    Usage(String),
+    #[error("{0}")]
+    Io(String),
+    /// The puzzle input could not be parsed.
+    #[error("{0}")]
+    Parse(input::Error),
+    /// One of the tool's own files, like a config file or answer store, is malformed.
+    #[error("invalid file {}: {source}", path.display())]
+    InvalidFile { path: PathBuf, source: input::Error },
+    /// A part could not be solved from the puzzle input.
+    #[error("{0}")]
+    Solve(input::Error),
+    #[error("{0}")]
+    Network(String),
+    /// Everything ran, but the outcome was a failure, e.g. an answer that does not verify.
+    #[error("{0}")]
+    Failed(String),
+}
+
+/// Splits a day's errors into bad input and failures to solve.
+impl From<input::Error> for AppError {
+    fn from(err: input::Error) -> Self {
+        match err {
+            input::Error::ParseError(_) | input::Error::PositionedParseError { .. } => {
+                AppError::Parse(err)
+            }
+            input::Error::LogicError(_) => AppError::Solve(err),
+        }
+    }
+}
+
+impl AppError {
+    /// An I/O failure while doing `action`, e.g. "could not read data/day5/large.txt".
+    pub fn io(action: impl Display, err: impl Display) -> AppError {
+        AppError::Io(format!("{}: {}", action, err))
+    }
+
+    pub fn invalid_file(path: &Path, source: input::Error) -> AppError {
+        AppError::InvalidFile {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn exit_code(&self) -> ExitCode {
+        let code = match self {
+            AppError::Failed(_) => 1,
+            AppError::Usage(_) => 2,
+            AppError::Io(_) => 3,
+            AppError::Parse(_) | AppError::InvalidFile { .. } => 4,
+            AppError::Solve(_) => 5,
+            AppError::Network(_) => 6,
+        };
+        ExitCode::from(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_codes_are_distinct_per_kind() {
+        let parse_error = || input::Error::ParseError("bad".to_string());
+        let errors = [
+            AppError::Failed("wrong".to_string()),
+            AppError::Usage("no day".to_string()),
+            AppError::Io("no file".to_string()),
+            AppError::Parse(parse_error()),
+            AppError::Solve(input::Error::LogicError("stuck".to_string())),
+            AppError::Network("offline".to_string()),
+        ];
+        let codes: Vec<String> = errors
+            .iter()
+            .map(|err| format!("{:?}", err.exit_code()))
+            .collect();
+        for (i, code) in codes.iter().enumerate() {
+            assert!(!codes[..i].contains(code), "{} is reused", code);
+        }
+
+        assert_eq!(
+            AppError::invalid_file(Path::new("answers.tsv"), parse_error()).exit_code(),
+            AppError::Parse(parse_error()).exit_code()
+        );
+    }
+
+    #[test]
+    fn day_errors_split_into_parse_and_solve() {
+        assert!(matches!(
+            AppError::from(input::Error::ParseError("bad".to_string())),
+            AppError::Parse(_)
+        ));
+        assert!(matches!(
+            AppError::from(input::Error::LogicError("stuck".to_string())),
+            AppError::Solve(_)
+        ));
+    }
+
+    #[test]
+    fn io_error_message() {
+        assert_eq!(
+            AppError::io("could not read in.txt", "not found").to_string(),
+            "could not read in.txt: not found"
+        );
+    }
+}
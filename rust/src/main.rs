@@ -1,119 +1,542 @@
-use clap::Parser;
-use std::error::Error;
-use std::io::{self, Read};
+use clap::{ArgGroup, Parser, Subcommand};
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use crate::error::AppError;
+use crate::runner::{InputSource, OutputFormat};
+use crate::solver::{DynSolver, ParamSetting, Params, Part};
+
+mod answers;
+mod bench;
+mod client;
+mod config;
 mod days;
+mod error;
+mod runner;
+mod solver;
+mod submissions;
 mod utils;
+mod visualize;
 
 #[derive(Parser)]
 #[command(name = "aoc")]
 #[command(about = "Advent of Code 2025 solutions in Rust", long_about = None)]
+#[command(group(ArgGroup::new("batch").args(["all", "days"])))]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Day number (1-12)
-    #[arg(short, long)]
-    day: u8,
+    #[arg(short, long, required_unless_present_any = ["list", "all", "days"])]
+    day: Option<u8>,
 
     /// Part number (1 or 2)
-    #[arg(short, long)]
-    part: u8,
+    #[arg(short, long, required_unless_present_any = ["list", "all", "days"])]
+    part: Option<u8>,
+
+    /// List the implemented days and parts, then exit
+    #[arg(long)]
+    list: bool,
+
+    /// Run every implemented day and part against its data file
+    #[arg(long, conflicts_with_all = ["day", "part", "days"])]
+    all: bool,
+
+    /// Run the implemented days in a range like 3-9 against their data files
+    #[arg(long, value_parser = runner::parse_day_range, conflicts_with_all = ["day", "part"])]
+    days: Option<RangeInclusive<u8>>,
+
+    /// Use each day's small.txt instead of large.txt with --all or --days
+    #[arg(long, requires = "batch", conflicts_with = "dataset")]
+    small: bool,
+
+    /// Read input from data/dayN/<DATASET>.txt, e.g. small or large, instead of stdin
+    #[arg(long)]
+    dataset: Option<String>,
+
+    /// Read input from this file instead of stdin
+    #[arg(long, conflicts_with_all = ["dataset", "batch"])]
+    input: Option<PathBuf>,
+
+    /// Draw how the part is solved in the terminal, frame by frame, before printing its answer
+    #[arg(long, conflicts_with = "batch")]
+    visualize: bool,
+
+    /// Milliseconds between frames with --visualize
+    #[arg(long, default_value_t = 100, requires = "visualize")]
+    frame_delay: u64,
+
+    /// Save the --visualize drawing instead of showing it: an animated GIF for a .gif path, an
+    /// SVG for a .svg path, otherwise a directory of numbered text files
+    #[arg(long, requires = "visualize")]
+    export: Option<PathBuf>,
+
+    /// With --visualize, also draw each candidate that would have been the best so far but was
+    /// rejected, e.g. day 9's rectangles
+    #[arg(long, requires = "visualize")]
+    show_rejected: bool,
+
+    /// Print how the answer was reached to stderr, e.g. the batteries day 3 turns on in each bank
+    #[arg(long, conflicts_with = "batch")]
+    explain: bool,
+
+    /// Check day 5's ids as they are read, instead of reading the whole input first, so that id
+    /// lists of any size fit in memory
+    #[arg(long, conflicts_with_all = ["batch", "visualize"])]
+    stream: bool,
+
+    /// With --stream, print whether each id is fresh or spoiled to stderr before the count
+    #[arg(long, requires = "stream")]
+    print_ids: bool,
+
+    /// Set one of the day's params, like --param connections=10; see --list for each day's params
+    #[arg(long = "param", value_name = "NAME=VALUE", conflicts_with = "batch")]
+    params: Vec<ParamSetting>,
+
+    /// How to print answers
+    #[arg(long, value_enum, default_value_t = OutputFormat::Human)]
+    format: OutputFormat,
+
+    /// Directory containing the dayN data folders
+    #[arg(long, global = true, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/data"))]
+    data_dir: PathBuf,
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    env_logger::init();
+#[derive(Subcommand)]
+enum Command {
+    /// Check every dataset's answers against those recorded in data/dayN/answers.tsv
+    Verify {
+        /// Only verify the days in a range like 3-9
+        #[arg(long, value_parser = runner::parse_day_range)]
+        days: Option<RangeInclusive<u8>>,
 
-    let args = Args::parse();
+        /// Only verify this dataset, e.g. small or large
+        #[arg(long)]
+        dataset: Option<String>,
 
-    // Validate day and part
-    if args.day < 1 || args.day > 12 {
-        return Err(format!("Day must be between 1 and 12, got {}", args.day).into());
-    }
-    if args.part < 1 || args.part > 2 {
-        return Err(format!("Part must be 1 or 2, got {}", args.part).into());
+        /// Record the computed answers as correct instead of checking them
+        #[arg(long)]
+        record: bool,
+    },
+    /// Time one day and part over repeated runs against one of its data files
+    Bench {
+        /// Day number (1-12)
+        #[arg(short, long)]
+        day: u8,
+
+        /// Part number (1 or 2)
+        #[arg(short, long)]
+        part: u8,
+
+        /// The dataset to read from data/dayN/<dataset>.txt
+        #[arg(long, default_value = "large")]
+        dataset: String,
+
+        /// Read input from this file instead of a dataset
+        #[arg(long)]
+        input: Option<PathBuf>,
+
+        /// Untimed runs before measuring
+        #[arg(long, default_value_t = 3)]
+        warmup: usize,
+
+        /// Timed runs
+        #[arg(short = 'n', long, default_value_t = 20)]
+        repeats: usize,
+
+        /// Also write the results as JSON to this file
+        #[arg(long)]
+        json: Option<PathBuf>,
+
+        /// Set one of the day's params, like --param connections=10
+        #[arg(long = "param", value_name = "NAME=VALUE")]
+        params: Vec<ParamSetting>,
+    },
+    /// Download a day's puzzle input to data/dayN/large.txt, unless it is already there
+    Fetch {
+        /// Day number (1-12)
+        #[arg(short, long)]
+        day: u8,
+
+        /// Base URL of the website, overriding the config file and AOC_BASE_URL
+        #[arg(long)]
+        base_url: Option<String>,
+    },
+    /// Submit an answer, logging the attempt to data/dayN/submissions.tsv
+    Submit {
+        /// Day number (1-12)
+        #[arg(short, long)]
+        day: u8,
+
+        /// Part number (1 or 2)
+        #[arg(short, long)]
+        part: u8,
+
+        /// The answer to submit; computed from data/dayN/large.txt if omitted
+        answer: Option<String>,
+
+        /// Base URL of the website, overriding the config file and AOC_BASE_URL
+        #[arg(long)]
+        base_url: Option<String>,
+    },
+}
+
+fn part_from_number(part: u8) -> Result<Part, AppError> {
+    Part::from_number(part)
+        .ok_or_else(|| AppError::Usage(format!("Part must be 1 or 2, got {}", part)))
+}
+
+fn check_day(day: u8) -> Result<(), AppError> {
+    if !(1..=12).contains(&day) {
+        return Err(AppError::Usage(format!(
+            "Day must be between 1 and 12, got {}",
+            day
+        )));
     }
+    Ok(())
+}
 
-    // Read all input from stdin
-    println!("Reading input...");
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
+fn solver_for(day: u8) -> Result<&'static dyn DynSolver, AppError> {
+    days::solver_for(day).ok_or_else(|| AppError::Usage(format!("Day {} not implemented", day)))
+}
 
-    // Dispatch to correct solver
-    match (args.day, args.part) {
-        (3, 1) => {
-            let joltage = days::day3::find_best_total_joltage(&input, 2);
-            println!("Best total joltage: {}", joltage);
-            Ok(())
-        }
-        (3, 2) => {
-            let joltage = days::day3::find_best_total_joltage(&input, 12);
-            println!("Best total joltage: {}", joltage);
-            Ok(())
+fn list_solvers() {
+    for (day, solver) in days::SOLVERS {
+        let parts: Vec<String> = Part::ALL.iter().map(Part::to_string).collect();
+        println!("Day {}: parts {}", day, parts.join(", "));
+        for param in solver.params() {
+            println!("  --param {}", param);
         }
-        (4, 1) => {
-            let count_accessible_rolls = days::day4::count_accessible_rolls_of_paper(&input)?;
-            println!(
-                "Count accessible rolls of paper: {}",
-                count_accessible_rolls
-            );
-            Ok(())
-        }
-        (4, 2) => {
-            let count_removable_rolls = days::day4::count_total_removable_rolls_of_paper(&input)?;
-            println!("Count removable rolls of paper: {}", count_removable_rolls);
-            Ok(())
-        }
-        (5, 1) => {
-            let count_fresh = days::day5::count_fresh_ingredients(&input)?;
-            println!("{} ingredients are fresh", count_fresh);
-            Ok(())
-        }
-        (5, 2) => {
-            let count_fresh = days::day5::count_all_fresh_ids(&input)?;
-            println!("There are {} fresh IDs", count_fresh);
-            Ok(())
-        }
-        (6, 1) => {
-            let sum = days::day6::solve_and_sum_math_sheet(&input)?;
-            println!("Sum: {}", sum);
-            Ok(())
-        }
-        (6, 2) => {
-            let sum = days::day6::solve_and_sum_cephalopod_math_sheet(&input)?;
-            println!("Sum: {}", sum);
-            Ok(())
-        }
-        (7, 1) => {
-            let count = days::day7::count_beam_splits(&input)?;
-            println!("Num beam splits: {}", count);
-            Ok(())
+    }
+}
+
+fn verify(
+    args: &Args,
+    days: Option<RangeInclusive<u8>>,
+    dataset: Option<&str>,
+    record: bool,
+) -> Result<(), AppError> {
+    let verifications =
+        runner::verify_days(&args.data_dir, days.unwrap_or(1..=12), dataset, record)?;
+    print!("{}", runner::format_verifications(&verifications));
+    let num_failed = verifications.iter().filter(|v| v.is_failure()).count();
+    if num_failed > 0 {
+        return Err(AppError::Failed(format!(
+            "{} of {} answers failed verification",
+            num_failed,
+            verifications.len()
+        )));
+    }
+    Ok(())
+}
+
+fn bench(
+    args: &Args,
+    day: u8,
+    part: u8,
+    source: &InputSource,
+    settings: &[ParamSetting],
+    options: &bench::BenchOptions,
+    json: Option<&PathBuf>,
+) -> Result<(), AppError> {
+    let part = part_from_number(part)?;
+    let solver = solver_for(day)?;
+    let params = Params::resolve(solver.params(), part, source.dataset(), settings)?;
+    let input = source.read(&args.data_dir, day)?;
+
+    let report = bench::bench(
+        day,
+        solver,
+        part,
+        &params,
+        &source.describe(),
+        &input,
+        options,
+    )?;
+    print!("{}", report.format_summary());
+    if let Some(json_path) = json {
+        let action = || format!("could not write {}", json_path.display());
+        let json =
+            serde_json::to_string_pretty(&report).map_err(|err| AppError::io(action(), err))?;
+        fs::write(json_path, json + "\n").map_err(|err| AppError::io(action(), err))?;
+    }
+    Ok(())
+}
+
+fn visualize(
+    args: &Args,
+    day: u8,
+    solver: &dyn DynSolver,
+    part: Part,
+    input: &str,
+) -> Result<(), AppError> {
+    let options = visualize::VisualizeOptions {
+        show_rejected: args.show_rejected,
+    };
+    let parsed = solver.parse_input(&solver.normalize_input(input))?;
+    let visualization = solver
+        .visualize_part(part, parsed.as_ref(), &options)?
+        .ok_or_else(|| AppError::Usage(format!("Day {} has no visualization", day)))?;
+    let delay = Duration::from_millis(args.frame_delay);
+    match &args.export {
+        Some(path) => visualize::Export::for_path(path).write(&visualization, delay),
+        None => visualize::show(&visualization, delay),
+    }
+}
+
+fn explain(
+    args: &Args,
+    day: u8,
+    solver: &dyn DynSolver,
+    part: Part,
+    source: &InputSource,
+    input: &str,
+) -> Result<(), AppError> {
+    let params = Params::resolve(solver.params(), part, source.dataset(), &args.params)?;
+    let parsed = solver.parse_input(&solver.normalize_input(input))?;
+    let explanation = solver
+        .explain_part(part, parsed.as_ref(), &params)?
+        .ok_or_else(|| AppError::Usage(format!("Day {} has no explanation", day)))?;
+    let output = runner::format_explanation(&explanation, args.format)
+        .map_err(|err| AppError::io("could not format explanation", err))?;
+    // The explanation goes to stderr, so stdout holds only the answer in the chosen --format.
+    eprint!("{}", output);
+    Ok(())
+}
+
+fn stream(
+    args: &Args,
+    day: u8,
+    solver: &dyn DynSolver,
+    part: Part,
+    source: &InputSource,
+) -> Result<(), AppError> {
+    if (day, part) != (5, Part::One) {
+        return Err(AppError::Usage(
+            "Only day 5 part 1 can be streamed".to_string(),
+        ));
+    }
+    let params = Params::resolve(solver.params(), part, source.dataset(), &args.params)?;
+    let mode = days::day5::RangeMode::from_params(&params)?;
+    let reader = source.open(&args.data_dir, day)?;
+
+    let start = Instant::now();
+    // Ids go to stderr, so stdout holds only the count in the chosen --format.
+    let mut out = BufWriter::new(io::stderr().lock());
+    let count = days::day5::stream_fresh_ingredients(reader, mode, |id, freshness| {
+        if args.print_ids {
+            writeln!(out, "{}: {}", id, freshness)?;
         }
-        (7, 2) => {
-            let count = days::day7::count_timelines(&input)?;
-            println!("Num timelines: {}", count);
-            Ok(())
+        Ok(())
+    })?;
+    out.flush()
+        .map_err(|err| AppError::io("could not write ids", err))?;
+    drop(out);
+
+    let result = runner::RunResult {
+        day,
+        part,
+        answer: Ok(count.into()),
+        parse_time: Duration::ZERO,
+        solve_time: start.elapsed(),
+        input_hash: None,
+    };
+    print_results(&[result], args.format)
+}
+
+fn load_client(args: &Args, base_url: Option<&str>) -> Result<client::Client, AppError> {
+    let mut config = config::Config::load()?;
+    if let Some(base_url) = base_url {
+        config.base_url = base_url.to_string();
+    }
+    Ok(client::Client::new(config, &args.data_dir))
+}
+
+fn fetch(args: &Args, day: u8, base_url: Option<&str>) -> Result<(), AppError> {
+    check_day(day)?;
+
+    let client = load_client(args, base_url)?;
+    match client.fetch_input(day)? {
+        client::FetchOutcome::Cached(path) => println!("Already cached: {}", path.display()),
+        client::FetchOutcome::Downloaded(path) => println!("Downloaded: {}", path.display()),
+    }
+    Ok(())
+}
+
+fn submit(
+    args: &Args,
+    day: u8,
+    part: u8,
+    answer: Option<&str>,
+    base_url: Option<&str>,
+) -> Result<(), AppError> {
+    let part = part_from_number(part)?;
+    let answer = match answer {
+        Some(answer) => answer.to_string(),
+        None => {
+            let solver = solver_for(day)?;
+            let params = Params::resolve(solver.params(), part, Some(client::INPUT_DATASET), &[])?;
+            let input = InputSource::Dataset(client::INPUT_DATASET.to_string())
+                .read(&args.data_dir, day)?;
+            solver.run(part, &input, &params)?.to_string()
         }
-        (8, 1) => {
-            let points = days::day8::parse_points(&input)?;
-            let total =
-                days::day8::multiply_n_largest_circuits_after_m_connections(3, 1000, &points);
-            println!("Total: {}", total);
-            Ok(())
+    };
+    let answer = submissions::clean_answer(&answer)?.to_string();
+
+    let log_path = submissions::SubmissionLog::path_for_day(&args.data_dir, day);
+    let mut log = submissions::SubmissionLog::load(&log_path)?;
+    log.check(part, &answer)
+        .map_err(|reason| AppError::Failed(format!("Not submitting: {}", reason)))?;
+
+    let client = load_client(args, base_url)?;
+    let (outcome, message) = client.submit_answer(day, part, &answer)?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    log.append(
+        &log_path,
+        submissions::Submission {
+            timestamp,
+            part,
+            answer: answer.clone(),
+            outcome,
+        },
+    )?;
+    println!("{}: {}", outcome, message);
+
+    if outcome != submissions::SubmissionOutcome::Correct {
+        return Err(AppError::Failed(format!(
+            "{} was not accepted ({})",
+            answer, outcome
+        )));
+    }
+    let answers_path = answers::AnswerStore::path_for_day(&args.data_dir, day);
+    let mut store = answers::AnswerStore::load(&answers_path)?;
+    store.set(client::INPUT_DATASET, part, &answer);
+    store.save(&answers_path)?;
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    env_logger::init();
+
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            err.exit_code()
         }
-        (8, 2) => {
-            let points = days::day8::parse_points(&input)?;
-            let total = days::day8::part2(&points);
-            println!("Total: {}", total);
-            Ok(())
+    }
+}
+
+fn run(args: &Args) -> Result<(), AppError> {
+    match &args.command {
+        Some(Command::Verify {
+            days,
+            dataset,
+            record,
+        }) => return verify(args, days.clone(), dataset.as_deref(), *record),
+        Some(Command::Bench {
+            day,
+            part,
+            dataset,
+            input,
+            warmup,
+            repeats,
+            json,
+            params,
+        }) => {
+            let options = bench::BenchOptions {
+                warmup: *warmup,
+                repeats: *repeats,
+            };
+            let source = InputSource::choose(input.as_deref(), Some(dataset));
+            return bench(args, *day, *part, &source, params, &options, json.as_ref());
         }
-        (9, 1) => {
-            let largest_area = days::day9::part1(&input)?;
-            println!("Largest area: {}", largest_area);
-            Ok(())
+        Some(Command::Fetch { day, base_url }) => return fetch(args, *day, base_url.as_deref()),
+        Some(Command::Submit {
+            day,
+            part,
+            answer,
+            base_url,
+        }) => {
+            return submit(args, *day, *part, answer.as_deref(), base_url.as_deref());
         }
-        (9, 2) => {
-            let largest_area = days::day9::part2(&input)?;
-            println!("Largest area: {}", largest_area);
-            Ok(())
+        None => {}
+    }
+
+    if args.list {
+        list_solvers();
+        return Ok(());
+    }
+
+    let day_range = if args.all {
+        Some(1..=12)
+    } else {
+        args.days.clone()
+    };
+    if let Some(day_range) = day_range {
+        let dataset = match (&args.dataset, args.small) {
+            (Some(dataset), _) => dataset.as_str(),
+            (None, true) => "small",
+            (None, false) => "large",
+        };
+        let results = runner::run_days(&args.data_dir, day_range, dataset);
+        print_results(&results, args.format)?;
+        let failures: Vec<&AppError> = results
+            .iter()
+            .filter_map(|r| r.answer.as_ref().err())
+            .collect();
+        if let Some(&first) = failures.first() {
+            eprintln!("{} of {} parts failed", failures.len(), results.len());
+            return Err(first.clone());
         }
-        _ => Err(format!("Day {} part {} not implemented", args.day, args.part).into()),
+        return Ok(());
     }
+
+    let day = args
+        .day
+        .ok_or_else(|| AppError::Usage("Day is required".to_string()))?;
+    let part = args
+        .part
+        .ok_or_else(|| AppError::Usage("Part is required".to_string()))?;
+
+    // Validate day and part
+    check_day(day)?;
+    let part = part_from_number(part)?;
+    let solver = solver_for(day)?;
+
+    let source = InputSource::choose(args.input.as_deref(), args.dataset.as_deref());
+    if args.stream {
+        return stream(args, day, solver, part, &source);
+    }
+    let input = source.read(&args.data_dir, day)?;
+
+    if args.visualize {
+        visualize(args, day, solver, part, &input)?;
+    }
+    if args.explain {
+        explain(args, day, solver, part, &source, &input)?;
+    }
+
+    let results = runner::run_input(day, solver, &input, &[part], source.dataset(), &args.params);
+    print_results(&results, args.format)?;
+    if let Some(Err(err)) = results.first().map(|r| &r.answer) {
+        return Err(err.clone());
+    }
+    Ok(())
+}
+
+fn print_results(results: &[runner::RunResult], format: OutputFormat) -> Result<(), AppError> {
+    let output = runner::format_results(results, format)
+        .map_err(|err| AppError::io("could not format results", err))?;
+    print!("{}", output);
+    Ok(())
 }
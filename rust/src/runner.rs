@@ -0,0 +1,759 @@
+// Runs registered solvers against the data files under `data/`
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use serde::Serialize;
+use serde::ser::{SerializeMap, Serializer};
+use sha2::{Digest, Sha256};
+
+use crate::answers::AnswerStore;
+use crate::days;
+use crate::error::AppError;
+use crate::solver::{Answer, DynSolver, Explanation, ParamSetting, Params, Part};
+
+/// The outcome of running one part of one day.
+pub struct RunResult {
+    pub day: u8,
+    pub part: Part,
+    /// The answer, or what went wrong.
+    pub answer: Result<Answer, AppError>,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+    /// The hex SHA-256 of the input, if it could be read.
+    pub input_hash: Option<String>,
+}
+
+/// How results are written to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// Sentences for a single part, or a table for several.
+    Human,
+    /// Only the answers, one per line.
+    Plain,
+    /// A JSON object for a single part, or an array of them for several.
+    Json,
+}
+
+/// The JSON form of a [`RunResult`].
+#[derive(Serialize)]
+struct RunRecord<'a> {
+    day: u8,
+    part: u8,
+    answer: Option<String>,
+    error: Option<String>,
+    parse_ns: u64,
+    solve_ns: u64,
+    input_sha256: Option<&'a str>,
+}
+
+impl RunResult {
+    fn to_record(&self) -> RunRecord<'_> {
+        RunRecord {
+            day: self.day,
+            part: self.part.number(),
+            answer: self.answer.as_ref().ok().map(Answer::to_string),
+            error: self.answer.as_ref().err().map(AppError::to_string),
+            parse_ns: self.parse_time.as_nanos() as u64,
+            solve_ns: self.solve_time.as_nanos() as u64,
+            input_sha256: self.input_hash.as_deref(),
+        }
+    }
+}
+
+/// How a computed answer compares with the recorded one.
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Correct,
+    Incorrect { expected: String },
+    Unrecorded,
+    Recorded,
+    Failed,
+}
+
+pub struct Verification {
+    pub dataset: String,
+    pub result: RunResult,
+    pub verdict: Verdict,
+}
+
+impl Verification {
+    pub fn is_failure(&self) -> bool {
+        matches!(self.verdict, Verdict::Incorrect { .. } | Verdict::Failed)
+    }
+}
+
+/// Parses a day selection like `5` or `3-9`.
+pub fn parse_day_range(input: &str) -> Result<RangeInclusive<u8>, String> {
+    let parse_day = |day: &str| {
+        day.trim()
+            .parse::<u8>()
+            .map_err(|err| format!("invalid day {}: {}", day, err))
+    };
+    let range = match input.split_once('-') {
+        Some((start, end)) => parse_day(start)?..=parse_day(end)?,
+        None => {
+            let day = parse_day(input)?;
+            day..=day
+        }
+    };
+    if range.is_empty() {
+        return Err(format!("day range {} is empty", input));
+    }
+    Ok(range)
+}
+
+pub fn data_file_path(data_dir: &Path, day: u8, dataset: &str) -> PathBuf {
+    data_dir
+        .join(format!("day{}", day))
+        .join(format!("{}.txt", dataset))
+}
+
+/// Where to read a day's input from.
+#[derive(Debug, PartialEq)]
+pub enum InputSource {
+    File(PathBuf),
+    /// A named dataset, read from `data/dayN/<name>.txt`.
+    Dataset(String),
+    Stdin,
+}
+
+impl InputSource {
+    /// Picks the input file if given, else the named dataset, else stdin.
+    pub fn choose(file: Option<&Path>, dataset: Option<&str>) -> InputSource {
+        match (file, dataset) {
+            (Some(file), _) => InputSource::File(file.to_path_buf()),
+            (None, Some(dataset)) => InputSource::Dataset(dataset.to_string()),
+            (None, None) => InputSource::Stdin,
+        }
+    }
+
+    /// The dataset's name, which some days' params depend on.
+    pub fn dataset(&self) -> Option<&str> {
+        match self {
+            InputSource::Dataset(name) => Some(name),
+            InputSource::File(_) | InputSource::Stdin => None,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            InputSource::File(path) => path.display().to_string(),
+            InputSource::Dataset(name) => name.clone(),
+            InputSource::Stdin => "stdin".to_string(),
+        }
+    }
+
+    /// The file to read, or None for stdin.
+    fn path(&self, data_dir: &Path, day: u8) -> Option<PathBuf> {
+        match self {
+            InputSource::File(path) => Some(path.clone()),
+            InputSource::Dataset(name) => Some(data_file_path(data_dir, day, name)),
+            InputSource::Stdin => None,
+        }
+    }
+
+    /// Opens the input to be read a line at a time, without reading all of it into memory.
+    pub fn open(&self, data_dir: &Path, day: u8) -> Result<Box<dyn BufRead>, AppError> {
+        let Some(path) = self.path(data_dir, day) else {
+            log::info!("Reading input from stdin...");
+            return Ok(Box::new(io::stdin().lock()));
+        };
+        log::info!("Streaming input from {}", path.display());
+        let file = fs::File::open(&path)
+            .map_err(|err| AppError::io(format!("could not read {}", path.display()), err))?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+
+    pub fn read(&self, data_dir: &Path, day: u8) -> Result<String, AppError> {
+        let Some(path) = self.path(data_dir, day) else {
+            log::info!("Reading input from stdin...");
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|err| AppError::io("could not read stdin", err))?;
+            return Ok(input);
+        };
+        log::info!("Reading input from {}", path.display());
+        fs::read_to_string(&path)
+            .map_err(|err| AppError::io(format!("could not read {}", path.display()), err))
+    }
+}
+
+/// Lists the names of the datasets for a day, i.e. each `data/dayN/<name>.txt` file.
+pub fn list_datasets(data_dir: &Path, day: u8) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(data_dir.join(format!("day{}", day))) else {
+        return Vec::new();
+    };
+    let mut datasets: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "txt" {
+                return None;
+            }
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .collect();
+    datasets.sort();
+    datasets
+}
+
+/// Runs both parts of every registered day in `days`, reading input from the given dataset.
+pub fn run_days(data_dir: &Path, days: RangeInclusive<u8>, dataset: &str) -> Vec<RunResult> {
+    let mut results = Vec::new();
+    for &(day, solver) in days::SOLVERS.iter().filter(|(day, _)| days.contains(day)) {
+        let path = data_file_path(data_dir, day, dataset);
+        log::info!("Running day {} with {}", day, path.display());
+        let input = match fs::read_to_string(&path) {
+            Ok(input) => input,
+            Err(err) => {
+                for part in Part::ALL {
+                    results.push(RunResult {
+                        day,
+                        part,
+                        answer: Err(AppError::io(
+                            format!("could not read {}", path.display()),
+                            &err,
+                        )),
+                        parse_time: Duration::ZERO,
+                        solve_time: Duration::ZERO,
+                        input_hash: None,
+                    });
+                }
+                continue;
+            }
+        };
+
+        results.extend(run_input(
+            day,
+            solver,
+            &input,
+            &Part::ALL,
+            Some(dataset),
+            &[],
+        ));
+    }
+    results
+}
+
+fn hash_input(input: &str) -> String {
+    Sha256::digest(input.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Normalizes and parses the input once, then solves each of `parts` from it, with the day's
+/// params for the dataset (if the input is one) overridden by `settings`.
+///
+/// The hash is of the raw input, while error positions refer to the normalized input.
+pub fn run_input(
+    day: u8,
+    solver: &dyn DynSolver,
+    input: &str,
+    parts: &[Part],
+    dataset: Option<&str>,
+    settings: &[ParamSetting],
+) -> Vec<RunResult> {
+    let input_hash = hash_input(input);
+
+    let start = Instant::now();
+    let input = solver.normalize_input(input);
+    let input = input.as_str();
+    let parsed = solver.parse_input(input);
+    let parse_time = start.elapsed();
+    if let Err(err) = &parsed
+        && err.position().is_some()
+    {
+        log::error!("Day {} input is invalid:\n{}", day, err.render(input));
+    }
+
+    parts
+        .iter()
+        .map(|&part| {
+            let start = Instant::now();
+            let answer = match &parsed {
+                Ok(parsed) => {
+                    Params::resolve(solver.params(), part, dataset, settings).and_then(|params| {
+                        let answer = solver.solve_part(part, parsed.as_ref(), &params);
+                        // Some days only read their input while solving, so their errors can
+                        // point into it too.
+                        if let Err(err) = &answer
+                            && err.position().is_some()
+                        {
+                            log::error!("Day {} input is invalid:\n{}", day, err.render(input));
+                        }
+                        answer.map_err(AppError::from)
+                    })
+                }
+                Err(err) => Err(AppError::from(err.clone())),
+            };
+            let solve_time = start.elapsed();
+            RunResult {
+                day,
+                part,
+                answer,
+                parse_time,
+                solve_time,
+                input_hash: Some(input_hash.clone()),
+            }
+        })
+        .collect()
+}
+
+fn compare_with_recorded(result: &RunResult, recorded: Option<&str>) -> Verdict {
+    match (&result.answer, recorded) {
+        (Err(_), _) => Verdict::Failed,
+        (Ok(_), None) => Verdict::Unrecorded,
+        (Ok(answer), Some(expected)) if answer.to_string() == expected => Verdict::Correct,
+        (Ok(_), Some(expected)) => Verdict::Incorrect {
+            expected: expected.to_string(),
+        },
+    }
+}
+
+/// Runs the registered days in `days` against each of their datasets, or only `dataset` if given,
+/// and compares the answers with those recorded in `data/dayN/answers.tsv`.
+///
+/// With `record`, successful answers are written to the answers file instead of being checked.
+pub fn verify_days(
+    data_dir: &Path,
+    days: RangeInclusive<u8>,
+    dataset: Option<&str>,
+    record: bool,
+) -> Result<Vec<Verification>, AppError> {
+    let mut verifications = Vec::new();
+    for &(day, _) in days::SOLVERS.iter().filter(|(day, _)| days.contains(day)) {
+        let answers_path = AnswerStore::path_for_day(data_dir, day);
+        let mut store = AnswerStore::load(&answers_path)?;
+        let datasets = match dataset {
+            Some(dataset) => vec![dataset.to_string()],
+            None => list_datasets(data_dir, day),
+        };
+        for dataset in datasets {
+            for result in run_days(data_dir, day..=day, &dataset) {
+                let verdict = match &result.answer {
+                    Ok(answer) if record => {
+                        store.set(&dataset, result.part, &answer.to_string());
+                        Verdict::Recorded
+                    }
+                    _ => compare_with_recorded(&result, store.get(&dataset, result.part)),
+                };
+                verifications.push(Verification {
+                    dataset: dataset.clone(),
+                    result,
+                    verdict,
+                });
+            }
+        }
+        if record {
+            store.save(&answers_path)?;
+        }
+    }
+    Ok(verifications)
+}
+
+/// Formats rows of cells as a table with left-aligned columns. Every row has a cell per column.
+fn format_table<R: AsRef<[String]>>(header: &[&str], rows: &[R]) -> String {
+    let header: Vec<String> = header.iter().map(|cell| cell.to_string()).collect();
+
+    let mut widths: Vec<usize> = header.iter().map(|cell| cell.chars().count()).collect();
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.as_ref()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    for row in std::iter::once(header.as_slice()).chain(rows.iter().map(R::as_ref)) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, &width)| format!("{:<width$}", cell))
+            .collect();
+        table.push_str(cells.join(" | ").trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+fn format_answer(result: &RunResult) -> String {
+    match &result.answer {
+        Ok(answer) => answer.to_string(),
+        Err(err) => format!("error: {}", err),
+    }
+}
+
+/// Formats the results as a table with one row per day and part.
+pub fn format_run_results(results: &[RunResult]) -> String {
+    let rows: Vec<[String; 5]> = results
+        .iter()
+        .map(|result| {
+            [
+                result.day.to_string(),
+                result.part.to_string(),
+                format_answer(result),
+                format!("{:.2?}", result.parse_time),
+                format!("{:.2?}", result.solve_time),
+            ]
+        })
+        .collect();
+    format_table(&["Day", "Part", "Answer", "Parse", "Solve"], &rows)
+}
+
+/// The JSON form of a row of an [`Explanation`], keyed by column in the order of the columns.
+struct ExplanationRow<'a> {
+    columns: &'a [&'static str],
+    cells: &'a [String],
+}
+
+impl Serialize for ExplanationRow<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+        for (column, cell) in self.columns.iter().zip(self.cells) {
+            map.serialize_entry(column, cell)?;
+        }
+        map.end()
+    }
+}
+
+/// Formats an explanation for stdout: a table, or for JSON an array with an object per row.
+pub fn format_explanation(
+    explanation: &Explanation,
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Human | OutputFormat::Plain => {
+            Ok(format_table(&explanation.columns, &explanation.rows))
+        }
+        OutputFormat::Json => {
+            let rows: Vec<ExplanationRow> = explanation
+                .rows
+                .iter()
+                .map(|cells| ExplanationRow {
+                    columns: &explanation.columns,
+                    cells,
+                })
+                .collect();
+            Ok(serde_json::to_string_pretty(&rows)? + "\n")
+        }
+    }
+}
+
+/// Formats the results for stdout in the given format.
+pub fn format_results(
+    results: &[RunResult],
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Human => Ok(match results {
+            [result] => match &result.answer {
+                Ok(answer) => format!("Day {} part {}: {}\n", result.day, result.part, answer),
+                Err(err) => format!("Day {} part {} failed: {}\n", result.day, result.part, err),
+            },
+            _ => format_run_results(results),
+        }),
+        OutputFormat::Plain => Ok(results
+            .iter()
+            .filter_map(|result| result.answer.as_ref().ok())
+            .map(|answer| format!("{}\n", answer))
+            .collect()),
+        OutputFormat::Json => {
+            let json = match results {
+                [result] => serde_json::to_string_pretty(&result.to_record())?,
+                _ => serde_json::to_string_pretty(
+                    &results.iter().map(RunResult::to_record).collect::<Vec<_>>(),
+                )?,
+            };
+            Ok(json + "\n")
+        }
+    }
+}
+
+/// Formats the verifications as a table with one row per day, dataset and part.
+pub fn format_verifications(verifications: &[Verification]) -> String {
+    let rows: Vec<[String; 5]> = verifications
+        .iter()
+        .map(|verification| {
+            let status = match &verification.verdict {
+                Verdict::Correct => "ok".to_string(),
+                Verdict::Incorrect { expected } => format!("WRONG (expected {})", expected),
+                Verdict::Unrecorded => "unrecorded".to_string(),
+                Verdict::Recorded => "recorded".to_string(),
+                Verdict::Failed => "FAILED".to_string(),
+            };
+            [
+                verification.result.day.to_string(),
+                verification.dataset.clone(),
+                verification.result.part.to_string(),
+                status,
+                format_answer(&verification.result),
+            ]
+        })
+        .collect();
+    format_table(&["Day", "Dataset", "Part", "Status", "Answer"], &rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::input::Error;
+
+    #[test]
+    fn input_source_prefers_file_then_dataset() {
+        assert_eq!(
+            InputSource::choose(Some(Path::new("in.txt")), Some("small")),
+            InputSource::File(PathBuf::from("in.txt"))
+        );
+        assert_eq!(
+            InputSource::choose(None, Some("small")),
+            InputSource::Dataset("small".to_string())
+        );
+        assert_eq!(InputSource::choose(None, None), InputSource::Stdin);
+    }
+
+    #[test]
+    fn input_source_reads_dataset() {
+        let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+        let input = InputSource::Dataset("small".to_string())
+            .read(&data_dir, 3)
+            .unwrap();
+
+        assert!(input.starts_with("987654321111111"));
+        assert!(
+            InputSource::Dataset("missing".to_string())
+                .read(&data_dir, 3)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn input_source_opens_dataset() {
+        let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+        let mut first_line = String::new();
+        InputSource::Dataset("small".to_string())
+            .open(&data_dir, 3)
+            .unwrap()
+            .read_line(&mut first_line)
+            .unwrap();
+
+        assert_eq!(first_line.trim_end(), "987654321111111");
+        assert!(
+            InputSource::Dataset("missing".to_string())
+                .open(&data_dir, 3)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parse_day_range_single() {
+        assert_eq!(parse_day_range("5"), Ok(5..=5));
+    }
+
+    #[test]
+    fn parse_day_range_span() {
+        assert_eq!(parse_day_range("3-9"), Ok(3..=9));
+    }
+
+    #[test]
+    fn parse_day_range_invalid() {
+        assert!(parse_day_range("9-3").is_err());
+        assert!(parse_day_range("a-3").is_err());
+        assert!(parse_day_range("").is_err());
+    }
+
+    #[test]
+    fn format_run_results_aligns_columns() {
+        let results = [
+            RunResult {
+                day: 3,
+                part: Part::One,
+                answer: Ok(Answer::Integer(357)),
+                parse_time: Duration::from_micros(1),
+                solve_time: Duration::from_micros(20),
+                input_hash: None,
+            },
+            RunResult {
+                day: 3,
+                part: Part::Two,
+                answer: Err(AppError::Io("oops".to_string())),
+                parse_time: Duration::from_micros(1),
+                solve_time: Duration::ZERO,
+                input_hash: None,
+            },
+        ];
+
+        assert_eq!(
+            format_run_results(&results),
+            "Day | Part | Answer      | Parse  | Solve
+3   | 1    | 357         | 1.00µs | 20.00µs
+3   | 2    | error: oops | 1.00µs | 0.00ns
+"
+        );
+    }
+
+    fn result_with_answer(answer: Result<i128, &str>) -> RunResult {
+        RunResult {
+            day: 5,
+            part: Part::One,
+            answer: answer
+                .map(Answer::Integer)
+                .map_err(|err| AppError::Parse(Error::ParseError(err.to_string()))),
+            parse_time: Duration::ZERO,
+            solve_time: Duration::ZERO,
+            input_hash: None,
+        }
+    }
+
+    #[test]
+    fn compare_with_recorded_verdicts() {
+        assert_eq!(
+            compare_with_recorded(&result_with_answer(Ok(3)), Some("3")),
+            Verdict::Correct
+        );
+        assert_eq!(
+            compare_with_recorded(&result_with_answer(Ok(4)), Some("3")),
+            Verdict::Incorrect {
+                expected: "3".to_string()
+            }
+        );
+        assert_eq!(
+            compare_with_recorded(&result_with_answer(Ok(4)), None),
+            Verdict::Unrecorded
+        );
+        assert_eq!(
+            compare_with_recorded(&result_with_answer(Err("bad input")), Some("3")),
+            Verdict::Failed
+        );
+    }
+
+    #[test]
+    fn run_input_hashes_input() {
+        let results = run_input(
+            3,
+            &crate::days::day3::Day3,
+            "987654321111111",
+            &[Part::One],
+            None,
+            &[],
+        );
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].answer, Ok(Answer::Integer(98)));
+        assert_eq!(
+            results[0].input_hash.as_deref(),
+            Some("2584c2b90af16e669e2a120754bd7d7d4c4344563598940d471ac86350295d8d")
+        );
+    }
+
+    #[test]
+    fn run_input_separates_parse_and_solve_failures() {
+        let day3 = &crate::days::day3::Day3;
+
+        let results = run_input(3, day3, "98765x", &[Part::One], None, &[]);
+        assert!(matches!(results[0].answer, Err(AppError::Parse(_))));
+
+        let results = run_input(3, day3, "9", &[Part::One], None, &[]);
+        assert!(matches!(results[0].answer, Err(AppError::Solve(_))));
+    }
+
+    #[test]
+    fn run_input_applies_params() {
+        let day3 = &crate::days::day3::Day3;
+        let input = "987654321111111";
+
+        let results = run_input(3, day3, input, &Part::ALL, None, &[]);
+        assert_eq!(results[0].answer, Ok(Answer::Integer(98)));
+        assert_eq!(results[1].answer, Ok(Answer::Integer(987654321111)));
+
+        let settings = ["batteries=3".parse().unwrap()];
+        let results = run_input(3, day3, input, &Part::ALL, None, &settings);
+        assert_eq!(results[0].answer, Ok(Answer::Integer(987)));
+        assert_eq!(results[1].answer, Ok(Answer::Integer(987)));
+
+        let settings = ["batterys=3".parse().unwrap()];
+        let results = run_input(3, day3, input, &[Part::One], None, &settings);
+        assert!(matches!(results[0].answer, Err(AppError::Usage(_))));
+    }
+
+    #[test]
+    fn run_input_applies_dataset_overrides() {
+        let day8 = &crate::days::day8::Day8;
+        let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+        let input = fs::read_to_string(data_file_path(&data_dir, 8, "small")).unwrap();
+
+        let results = run_input(8, day8, &input, &[Part::One], Some("small"), &[]);
+        assert_eq!(results[0].answer, Ok(Answer::Integer(40)));
+
+        let settings = [
+            "connections=1000".parse().unwrap(),
+            "circuits=1".parse().unwrap(),
+        ];
+        let results = run_input(8, day8, &input, &[Part::One], Some("small"), &settings);
+        assert_eq!(results[0].answer, Ok(Answer::Integer(20)));
+    }
+
+    #[test]
+    fn format_explanation_as_table_or_json() {
+        let explanation = Explanation {
+            columns: vec!["Line", "Joltage"],
+            rows: vec![
+                vec!["1".to_string(), "98".to_string()],
+                vec!["2".to_string(), "9".to_string()],
+            ],
+        };
+
+        assert_eq!(
+            format_explanation(&explanation, OutputFormat::Human).unwrap(),
+            "Line | Joltage\n1    | 98\n2    | 9\n"
+        );
+        let json = format_explanation(&explanation, OutputFormat::Json).unwrap();
+        assert!(json.find("Line").unwrap() < json.find("Joltage").unwrap());
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            serde_json::json!([
+                {"Line": "1", "Joltage": "98"},
+                {"Line": "2", "Joltage": "9"}
+            ])
+        );
+    }
+
+    #[test]
+    fn format_results_plain_prints_only_answers() {
+        let results = [
+            result_with_answer(Ok(3)),
+            result_with_answer(Err("bad input")),
+        ];
+
+        assert_eq!(
+            format_results(&results, OutputFormat::Plain).unwrap(),
+            "3\n"
+        );
+    }
+
+    #[test]
+    fn format_results_json_single_part() {
+        let mut result = result_with_answer(Ok(3));
+        result.input_hash = Some("abc".to_string());
+
+        let json = format_results(&[result], OutputFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "day": 5,
+                "part": 1,
+                "answer": "3",
+                "error": null,
+                "parse_ns": 0,
+                "solve_ns": 0,
+                "input_sha256": "abc",
+            })
+        );
+    }
+}
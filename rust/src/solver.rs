@@ -0,0 +1,488 @@
+// Common interface implemented by every day's solution
+
+use std::any::Any;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use num_bigint::BigInt;
+
+use crate::error::AppError;
+use crate::utils::input::{self, Error, Whitespace};
+use crate::visualize::{Visualization, VisualizeOptions};
+
+/// One of the two parts of a day's puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn from_number(number: u8) -> Option<Part> {
+        match number {
+            1 => Some(Part::One),
+            2 => Some(Part::Two),
+            _ => None,
+        }
+    }
+
+    pub fn number(&self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+/// The answer to one part of a puzzle.
+///
+/// Its `Display` rendering is canonical: integers are plain decimal with no separators, exponent or
+/// fractional part, so the rendered answer can be submitted as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Integer(i128),
+    /// An integer too large for `Integer`.
+    BigInteger(BigInt),
+    Text(String),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Integer(value) => write!(f, "{}", value),
+            Answer::BigInteger(value) => write!(f, "{}", value),
+            Answer::Text(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+macro_rules! answer_from_integer {
+    ($($int:ty),*) => {
+        $(
+            impl From<$int> for Answer {
+                fn from(value: $int) -> Self {
+                    Answer::Integer(value.into())
+                }
+            }
+        )*
+    };
+}
+
+answer_from_integer!(u8, u16, u32, u64, i8, i16, i32, i64, i128);
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        Answer::Integer(value as i128)
+    }
+}
+
+impl From<u128> for Answer {
+    fn from(value: u128) -> Self {
+        match i128::try_from(value) {
+            Ok(value) => Answer::Integer(value),
+            Err(_) => Answer::BigInteger(value.into()),
+        }
+    }
+}
+
+impl From<BigInt> for Answer {
+    fn from(value: BigInt) -> Self {
+        match i128::try_from(&value) {
+            Ok(value) => Answer::Integer(value),
+            Err(_) => Answer::BigInteger(value),
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value.trim().to_string())
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::Text(value.trim().to_string())
+    }
+}
+
+/// A named number that changes how a day solves its puzzle, like how many pairs to connect.
+#[derive(Debug)]
+pub struct Param {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub default: usize,
+    /// Values used instead of `default` for some parts or datasets. Later overrides take
+    /// precedence over earlier ones.
+    pub overrides: &'static [ParamOverride],
+}
+
+/// A value for a [`Param`] that only applies to one part, one dataset, or both.
+#[derive(Debug)]
+pub struct ParamOverride {
+    pub part: Option<Part>,
+    pub dataset: Option<&'static str>,
+    pub value: usize,
+}
+
+impl ParamOverride {
+    fn applies_to(&self, part: Part, dataset: Option<&str>) -> bool {
+        self.part.is_none_or(|p| p == part) && self.dataset.is_none_or(|d| Some(d) == dataset)
+    }
+}
+
+impl Display for Param {
+    /// Describes the param and its defaults,
+    /// e.g. `connections: pairs to connect (1000; small: 10)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ({}", self.name, self.description, self.default)?;
+        for param_override in self.overrides {
+            let mut conditions = Vec::new();
+            if let Some(part) = param_override.part {
+                conditions.push(format!("part {}", part));
+            }
+            if let Some(dataset) = param_override.dataset {
+                conditions.push(dataset.to_string());
+            }
+            write!(f, "; {}: {}", conditions.join(", "), param_override.value)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// A param value given on the command line as `name=value`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamSetting {
+    pub name: String,
+    pub value: usize,
+}
+
+impl FromStr for ParamSetting {
+    type Err = String;
+
+    fn from_str(setting: &str) -> Result<ParamSetting, String> {
+        let (name, value) = setting
+            .split_once('=')
+            .ok_or_else(|| format!("expected name=value, got {}", setting))?;
+        let value = value
+            .trim()
+            .parse()
+            .map_err(|err| format!("invalid value for {}: {}", name, err))?;
+        Ok(ParamSetting {
+            name: name.trim().to_string(),
+            value,
+        })
+    }
+}
+
+/// The value of each of a day's params, for one part and dataset.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Params {
+    values: Vec<(&'static str, usize)>,
+}
+
+impl Params {
+    /// Works out each param's value: its default, then any overrides for this part and dataset,
+    /// then any setting for it. Settings for params the day does not have are an error.
+    pub fn resolve(
+        params: &'static [Param],
+        part: Part,
+        dataset: Option<&str>,
+        settings: &[ParamSetting],
+    ) -> Result<Params, AppError> {
+        if let Some(unknown) = settings
+            .iter()
+            .find(|setting| params.iter().all(|param| param.name != setting.name))
+        {
+            let names: Vec<&str> = params.iter().map(|param| param.name).collect();
+            return Err(AppError::Usage(match names.is_empty() {
+                true => format!("unknown param {}; this day has none", unknown.name),
+                false => format!(
+                    "unknown param {}; expected one of {}",
+                    unknown.name,
+                    names.join(", ")
+                ),
+            }));
+        }
+
+        let values = params
+            .iter()
+            .map(|param| {
+                let mut value = param.default;
+                for param_override in param.overrides {
+                    if param_override.applies_to(part, dataset) {
+                        value = param_override.value;
+                    }
+                }
+                for setting in settings.iter().filter(|s| s.name == param.name) {
+                    value = setting.value;
+                }
+                (param.name, value)
+            })
+            .collect();
+        Ok(Params { values })
+    }
+
+    /// The value of the named param, which the day must have declared.
+    pub fn get(&self, name: &str) -> Result<usize, Error> {
+        self.values
+            .iter()
+            .find(|(param_name, _)| *param_name == name)
+            .map(|&(_, value)| value)
+            .ok_or_else(|| Error::LogicError(format!("no param named {}", name)))
+    }
+}
+
+/// A table explaining how a part's answer was reached, e.g. what was chosen from each line of the
+/// input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub columns: Vec<&'static str>,
+    /// One row per explained item, with a cell for each column.
+    pub rows: Vec<Vec<String>>,
+}
+
+/// A day's solution. The input is parsed once, and either part can then be solved from it.
+pub trait Solver: Sync {
+    /// The parsed puzzle input, shared by both parts.
+    type Parsed: 'static;
+
+    /// Whether spaces in the input matter, and so must survive normalization.
+    const WHITESPACE: Whitespace = Whitespace::Insignificant;
+
+    /// The params the parts read from [`Params`].
+    const PARAMS: &'static [Param] = &[];
+
+    fn parse(&self, input: &str) -> Result<Self::Parsed, Error>;
+
+    fn part1(&self, parsed: &Self::Parsed, params: &Params) -> Result<Answer, Error>;
+
+    fn part2(&self, parsed: &Self::Parsed, params: &Params) -> Result<Answer, Error>;
+
+    /// A drawing of how a part is solved, for days that can draw themselves.
+    fn visualize(
+        &self,
+        _parsed: &Self::Parsed,
+        _part: Part,
+        _options: &VisualizeOptions,
+    ) -> Option<Visualization> {
+        None
+    }
+
+    /// How a part's answer was reached, for days that can explain themselves.
+    fn explain(
+        &self,
+        _parsed: &Self::Parsed,
+        _part: Part,
+        _params: &Params,
+    ) -> Result<Option<Explanation>, Error> {
+        Ok(None)
+    }
+}
+
+/// Object-safe form of [`Solver`], so that every day can live in one registry.
+pub trait DynSolver: Sync {
+    /// Cleans up raw input as this solver expects, ready for [`DynSolver::parse_input`].
+    fn normalize_input(&self, input: &str) -> String;
+
+    fn params(&self) -> &'static [Param];
+
+    fn parse_input(&self, input: &str) -> Result<Box<dyn Any>, Error>;
+
+    fn solve_part(&self, part: Part, parsed: &dyn Any, params: &Params) -> Result<Answer, Error>;
+
+    fn visualize_part(
+        &self,
+        part: Part,
+        parsed: &dyn Any,
+        options: &VisualizeOptions,
+    ) -> Result<Option<Visualization>, Error>;
+
+    fn explain_part(
+        &self,
+        part: Part,
+        parsed: &dyn Any,
+        params: &Params,
+    ) -> Result<Option<Explanation>, Error>;
+
+    /// Normalizes and parses the raw input, then solves the given part.
+    fn run(&self, part: Part, input: &str, params: &Params) -> Result<Answer, Error> {
+        let parsed = self.parse_input(&self.normalize_input(input))?;
+        self.solve_part(part, parsed.as_ref(), params)
+    }
+}
+
+impl<S: Solver> DynSolver for S {
+    fn normalize_input(&self, input: &str) -> String {
+        input::normalize(input, S::WHITESPACE)
+    }
+
+    fn params(&self) -> &'static [Param] {
+        S::PARAMS
+    }
+
+    fn parse_input(&self, input: &str) -> Result<Box<dyn Any>, Error> {
+        Ok(Box::new(self.parse(input)?))
+    }
+
+    fn solve_part(&self, part: Part, parsed: &dyn Any, params: &Params) -> Result<Answer, Error> {
+        let parsed = downcast_parsed::<S>(parsed)?;
+        match part {
+            Part::One => self.part1(parsed, params),
+            Part::Two => self.part2(parsed, params),
+        }
+    }
+
+    fn visualize_part(
+        &self,
+        part: Part,
+        parsed: &dyn Any,
+        options: &VisualizeOptions,
+    ) -> Result<Option<Visualization>, Error> {
+        Ok(self.visualize(downcast_parsed::<S>(parsed)?, part, options))
+    }
+
+    fn explain_part(
+        &self,
+        part: Part,
+        parsed: &dyn Any,
+        params: &Params,
+    ) -> Result<Option<Explanation>, Error> {
+        self.explain(downcast_parsed::<S>(parsed)?, part, params)
+    }
+}
+
+fn downcast_parsed<S: Solver>(parsed: &dyn Any) -> Result<&S::Parsed, Error> {
+    parsed
+        .downcast_ref::<S::Parsed>()
+        .ok_or_else(|| Error::LogicError("parsed input does not belong to this solver".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answer_renders_integers_exactly() {
+        assert_eq!(Answer::from(25272u64).to_string(), "25272");
+        assert_eq!(Answer::from(-12i64).to_string(), "-12");
+        assert_eq!(Answer::from(u64::MAX).to_string(), "18446744073709551615");
+    }
+
+    #[test]
+    fn answer_from_large_integers() {
+        assert_eq!(
+            Answer::from(u128::MAX),
+            Answer::BigInteger(BigInt::from(u128::MAX))
+        );
+        assert_eq!(Answer::from(BigInt::from(42)), Answer::Integer(42));
+        assert_eq!(
+            Answer::from(BigInt::from(u128::MAX) * 10).to_string(),
+            "3402823669209384634633746074317682114550"
+        );
+    }
+
+    #[test]
+    fn answer_text_is_trimmed() {
+        assert_eq!(Answer::from(" abc\n").to_string(), "abc");
+    }
+
+    const EXAMPLE_PARAMS: &[Param] = &[
+        Param {
+            name: "connections",
+            description: "pairs to connect",
+            default: 1000,
+            overrides: &[ParamOverride {
+                part: None,
+                dataset: Some("small"),
+                value: 10,
+            }],
+        },
+        Param {
+            name: "batteries",
+            description: "batteries per bank",
+            default: 2,
+            overrides: &[ParamOverride {
+                part: Some(Part::Two),
+                dataset: None,
+                value: 12,
+            }],
+        },
+    ];
+
+    fn resolve(part: Part, dataset: Option<&str>, settings: &[&str]) -> Result<Params, AppError> {
+        let settings: Vec<ParamSetting> = settings.iter().map(|s| s.parse().unwrap()).collect();
+        Params::resolve(EXAMPLE_PARAMS, part, dataset, &settings)
+    }
+
+    #[test]
+    fn params_use_defaults_then_overrides() {
+        let params = resolve(Part::One, Some("large"), &[]).unwrap();
+        assert_eq!(params.get("connections"), Ok(1000));
+        assert_eq!(params.get("batteries"), Ok(2));
+
+        let params = resolve(Part::Two, Some("small"), &[]).unwrap();
+        assert_eq!(params.get("connections"), Ok(10));
+        assert_eq!(params.get("batteries"), Ok(12));
+
+        let params = resolve(Part::One, None, &[]).unwrap();
+        assert_eq!(params.get("connections"), Ok(1000));
+    }
+
+    #[test]
+    fn params_settings_win() {
+        let params = resolve(
+            Part::Two,
+            Some("small"),
+            &["connections=5", "batteries = 3"],
+        )
+        .unwrap();
+        assert_eq!(params.get("connections"), Ok(5));
+        assert_eq!(params.get("batteries"), Ok(3));
+    }
+
+    #[test]
+    fn params_reject_unknown_names() {
+        assert!(matches!(
+            resolve(Part::One, None, &["connectons=5"]),
+            Err(AppError::Usage(message)) if message.contains("connections, batteries")
+        ));
+        assert!(matches!(
+            Params::default().get("connections"),
+            Err(Error::LogicError(_))
+        ));
+    }
+
+    #[test]
+    fn param_setting_from_str() {
+        assert_eq!(
+            "connections=10".parse(),
+            Ok(ParamSetting {
+                name: "connections".to_string(),
+                value: 10
+            })
+        );
+        assert!("connections".parse::<ParamSetting>().is_err());
+        assert!("connections=-1".parse::<ParamSetting>().is_err());
+    }
+
+    #[test]
+    fn param_display() {
+        assert_eq!(
+            EXAMPLE_PARAMS[0].to_string(),
+            "connections: pairs to connect (1000; small: 10)"
+        );
+        assert_eq!(
+            EXAMPLE_PARAMS[1].to_string(),
+            "batteries: batteries per bank (2; part 2: 12)"
+        );
+    }
+}
@@ -0,0 +1,393 @@
+// Answer submissions and the local log of every attempt, in `data/dayN/submissions.tsv`
+
+use std::fmt::{self, Display};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::error::AppError;
+use crate::solver::Part;
+use crate::utils::input::Error as InputError;
+
+const SUBMISSIONS_FILE_NAME: &str = "submissions.tsv";
+
+/// The website's verdict on a submitted answer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SubmissionOutcome {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without a hint about which way.
+    Wrong,
+    /// Submitted too soon after a previous answer, so it was not checked.
+    RateLimited,
+    /// The response did not match any known message, e.g. because the part is already solved.
+    Unknown,
+}
+
+impl SubmissionOutcome {
+    /// Reads the outcome from the HTML page returned for a submission.
+    pub fn from_response(html: &str) -> SubmissionOutcome {
+        if html.contains("That's the right answer") {
+            SubmissionOutcome::Correct
+        } else if html.contains("You gave an answer too recently") {
+            SubmissionOutcome::RateLimited
+        } else if html.contains("That's not the right answer") {
+            if html.contains("your answer is too high") {
+                SubmissionOutcome::TooHigh
+            } else if html.contains("your answer is too low") {
+                SubmissionOutcome::TooLow
+            } else {
+                SubmissionOutcome::Wrong
+            }
+        } else {
+            SubmissionOutcome::Unknown
+        }
+    }
+
+    /// Whether the answer was checked and found to be wrong.
+    pub fn is_wrong(&self) -> bool {
+        matches!(
+            self,
+            SubmissionOutcome::TooHigh | SubmissionOutcome::TooLow | SubmissionOutcome::Wrong
+        )
+    }
+}
+
+impl Display for SubmissionOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SubmissionOutcome::Correct => "correct",
+            SubmissionOutcome::TooHigh => "too-high",
+            SubmissionOutcome::TooLow => "too-low",
+            SubmissionOutcome::Wrong => "wrong",
+            SubmissionOutcome::RateLimited => "rate-limited",
+            SubmissionOutcome::Unknown => "unknown",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for SubmissionOutcome {
+    type Err = InputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "correct" => Ok(SubmissionOutcome::Correct),
+            "too-high" => Ok(SubmissionOutcome::TooHigh),
+            "too-low" => Ok(SubmissionOutcome::TooLow),
+            "wrong" => Ok(SubmissionOutcome::Wrong),
+            "rate-limited" => Ok(SubmissionOutcome::RateLimited),
+            "unknown" => Ok(SubmissionOutcome::Unknown),
+            _ => Err(InputError::ParseError(format!(
+                "invalid submission outcome: {}",
+                s
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Submission {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub part: Part,
+    pub answer: String,
+    pub outcome: SubmissionOutcome,
+}
+
+impl Submission {
+    fn parse(line: &str) -> Result<Submission, InputError> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 4 {
+            return Err(InputError::ParseError(format!(
+                "submission must be a timestamp, part, answer and outcome split by tabs, found: {}",
+                line
+            )));
+        }
+        let timestamp = fields[0].parse().map_err(|err| {
+            InputError::ParseError(format!("invalid timestamp {}: {:?}", fields[0], err))
+        })?;
+        let part = fields[1]
+            .parse::<u8>()
+            .ok()
+            .and_then(Part::from_number)
+            .ok_or_else(|| InputError::ParseError(format!("invalid part: {}", fields[1])))?;
+        Ok(Submission {
+            timestamp,
+            part,
+            answer: fields[2].to_string(),
+            outcome: fields[3].parse()?,
+        })
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\n",
+            self.timestamp, self.part, self.answer, self.outcome
+        )
+    }
+}
+
+/// Trims the whitespace around an answer, and refuses one that is empty or has a tab, newline or
+/// other control character in it, since those would break the line it is logged on.
+pub fn clean_answer(answer: &str) -> Result<&str, AppError> {
+    let answer = answer.trim();
+    if answer.is_empty() {
+        return Err(AppError::Usage("answer is empty".to_string()));
+    }
+    if answer.chars().any(char::is_control) {
+        return Err(AppError::Usage(format!(
+            "answer {:?} must not contain tabs, newlines or other control characters",
+            answer
+        )));
+    }
+    Ok(answer)
+}
+
+/// Every answer submitted for one day, oldest first.
+#[derive(Debug, Default)]
+pub struct SubmissionLog {
+    submissions: Vec<Submission>,
+}
+
+impl SubmissionLog {
+    pub fn path_for_day(data_dir: &Path, day: u8) -> PathBuf {
+        data_dir
+            .join(format!("day{}", day))
+            .join(SUBMISSIONS_FILE_NAME)
+    }
+
+    /// Loads the log from `path`, or returns an empty log if nothing has been submitted yet.
+    pub fn load(path: &Path) -> Result<SubmissionLog, AppError> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(AppError::io(
+                    format!("could not read {}", path.display()),
+                    err,
+                ));
+            }
+        };
+        let submissions = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(Submission::parse)
+            .collect::<Result<_, _>>()
+            .map_err(|err| AppError::invalid_file(path, err))?;
+        Ok(SubmissionLog { submissions })
+    }
+
+    /// Adds the submission to the log and appends it to the file at `path`. Its answer is
+    /// cleaned with [`clean_answer`] first.
+    pub fn append(&mut self, path: &Path, mut submission: Submission) -> Result<(), AppError> {
+        submission.answer = clean_answer(&submission.answer)?.to_string();
+        let write = || -> io::Result<()> {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            file.write_all(submission.to_line().as_bytes())
+        };
+        write().map_err(|err| AppError::io(format!("could not write {}", path.display()), err))?;
+        self.submissions.push(submission);
+        Ok(())
+    }
+
+    /// Checks that submitting `answer` could be useful, or explains why it would not be.
+    ///
+    /// Answers are refused if the part is already solved, if the same answer was already wrong,
+    /// or if it is at or beyond a bound learned from a "too high" or "too low" reply.
+    pub fn check(&self, part: Part, answer: &str) -> Result<(), String> {
+        let for_part = || self.submissions.iter().filter(move |s| s.part == part);
+
+        if let Some(correct) = for_part().find(|s| s.outcome == SubmissionOutcome::Correct) {
+            return Err(format!(
+                "part {} is already solved with {}",
+                part, correct.answer
+            ));
+        }
+        if let Some(wrong) = for_part().find(|s| s.answer == answer && s.outcome.is_wrong()) {
+            return Err(format!(
+                "{} was already submitted and was {}",
+                answer, wrong.outcome
+            ));
+        }
+
+        let Ok(value) = answer.parse::<i128>() else {
+            return Ok(());
+        };
+        let bound = |outcome| {
+            for_part()
+                .filter(move |s| s.outcome == outcome)
+                .filter_map(|s| s.answer.parse::<i128>().ok())
+        };
+        if let Some(too_high) = bound(SubmissionOutcome::TooHigh).min()
+            && value >= too_high
+        {
+            return Err(format!(
+                "{} is not below {}, which was too high",
+                answer, too_high
+            ));
+        }
+        if let Some(too_low) = bound(SubmissionOutcome::TooLow).max()
+            && value <= too_low
+        {
+            return Err(format!(
+                "{} is not above {}, which was too low",
+                answer, too_low
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_of(submissions: &[(Part, &str, SubmissionOutcome)]) -> SubmissionLog {
+        SubmissionLog {
+            submissions: submissions
+                .iter()
+                .map(|&(part, answer, outcome)| Submission {
+                    timestamp: 0,
+                    part,
+                    answer: answer.to_string(),
+                    outcome,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn outcome_from_response() {
+        let cases = [
+            (
+                "<article><p>That's the right answer! You are one gold star closer.</p></article>",
+                SubmissionOutcome::Correct,
+            ),
+            (
+                "<article><p>That's not the right answer; your answer is too high.</p></article>",
+                SubmissionOutcome::TooHigh,
+            ),
+            (
+                "<article><p>That's not the right answer; your answer is too low.</p></article>",
+                SubmissionOutcome::TooLow,
+            ),
+            (
+                "<article><p>That's not the right answer. If you're stuck...</p></article>",
+                SubmissionOutcome::Wrong,
+            ),
+            (
+                "<article><p>You gave an answer too recently; you have to wait.</p></article>",
+                SubmissionOutcome::RateLimited,
+            ),
+            (
+                "<article><p>You don't seem to be solving the right level.</p></article>",
+                SubmissionOutcome::Unknown,
+            ),
+        ];
+        for (html, outcome) in cases {
+            assert_eq!(SubmissionOutcome::from_response(html), outcome, "{}", html);
+        }
+    }
+
+    #[test]
+    fn submission_line_round_trips() {
+        let submission = Submission {
+            timestamp: 1764547200,
+            part: Part::Two,
+            answer: "25272".to_string(),
+            outcome: SubmissionOutcome::TooLow,
+        };
+        let line = submission.to_line();
+
+        assert_eq!(line, "1764547200\t2\t25272\ttoo-low\n");
+        assert_eq!(Submission::parse(line.trim_end()).unwrap(), submission);
+    }
+
+    #[test]
+    fn check_refuses_known_wrong_answer() {
+        let log = log_of(&[(Part::One, "abc", SubmissionOutcome::Wrong)]);
+
+        assert!(log.check(Part::One, "abc").is_err());
+        assert!(log.check(Part::One, "abd").is_ok());
+        assert!(log.check(Part::Two, "abc").is_ok());
+    }
+
+    #[test]
+    fn check_refuses_answers_outside_bounds() {
+        let log = log_of(&[
+            (Part::One, "100", SubmissionOutcome::TooHigh),
+            (Part::One, "150", SubmissionOutcome::TooHigh),
+            (Part::One, "20", SubmissionOutcome::TooLow),
+        ]);
+
+        assert!(log.check(Part::One, "100").is_err());
+        assert!(log.check(Part::One, "120").is_err());
+        assert!(log.check(Part::One, "20").is_err());
+        assert!(log.check(Part::One, "5").is_err());
+        assert!(log.check(Part::One, "99").is_ok());
+        assert!(log.check(Part::One, "21").is_ok());
+        assert!(log.check(Part::Two, "500").is_ok());
+    }
+
+    #[test]
+    fn check_refuses_solved_part() {
+        let log = log_of(&[
+            (Part::One, "7", SubmissionOutcome::RateLimited),
+            (Part::One, "8", SubmissionOutcome::Correct),
+        ]);
+
+        assert!(log.check(Part::One, "8").is_err());
+        assert!(log.check(Part::One, "9").is_err());
+        assert!(log.check(Part::Two, "8").is_ok());
+    }
+
+    #[test]
+    fn check_allows_resubmitting_rate_limited_answer() {
+        let log = log_of(&[(Part::Two, "7", SubmissionOutcome::RateLimited)]);
+
+        assert!(log.check(Part::Two, "7").is_ok());
+    }
+
+    #[test]
+    fn clean_answer_trims_and_refuses_control_characters() {
+        assert_eq!(clean_answer(" 25272\n").ok(), Some("25272"));
+        assert_eq!(clean_answer("a b").ok(), Some("a b"));
+        for answer in ["25\t272", "25\n272", "25\r272", " \n"] {
+            assert!(
+                matches!(clean_answer(answer), Err(AppError::Usage(_))),
+                "{:?}",
+                answer
+            );
+        }
+    }
+
+    #[test]
+    fn append_keeps_the_log_readable() {
+        let dir = std::env::temp_dir().join(format!("aoc-submissions-{}", std::process::id()));
+        let path = dir.join(SUBMISSIONS_FILE_NAME);
+        let submission = |answer: &str| Submission {
+            timestamp: 1764547200,
+            part: Part::One,
+            answer: answer.to_string(),
+            outcome: SubmissionOutcome::Wrong,
+        };
+
+        let mut log = SubmissionLog::default();
+        log.append(&path, submission("42\n")).unwrap();
+        assert!(matches!(
+            log.append(&path, submission("4\t2")),
+            Err(AppError::Usage(_))
+        ));
+        let loaded = SubmissionLog::load(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded.submissions, vec![submission("42")]);
+        assert!(loaded.check(Part::One, "42").is_err());
+    }
+}
@@ -0,0 +1,169 @@
+// Union-find for grouping ids that are connected one pair at a time
+
+/// Disjoint sets of the ids `0..len`, which start apart and are merged pair by pair.
+///
+/// Finding a set's root compresses the path to it, and smaller sets are merged into larger ones,
+/// so any sequence of operations runs in near constant time per operation.
+#[derive(Clone, Debug)]
+pub struct DisjointSets {
+    parents: Vec<usize>,
+    /// The size of each set, only kept up to date for roots.
+    sizes: Vec<usize>,
+    num_sets: usize,
+}
+
+impl DisjointSets {
+    /// Puts each of the ids `0..len` in a set of its own.
+    pub fn new(len: usize) -> DisjointSets {
+        DisjointSets {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+            num_sets: len,
+        }
+    }
+
+    /// The number of ids.
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    pub fn num_sets(&self) -> usize {
+        self.num_sets
+    }
+
+    /// Returns the root id that represents `id`'s set.
+    pub fn find(&mut self, id: usize) -> usize {
+        let mut root = id;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        // Point everything on the path straight at the root.
+        let mut id = id;
+        while self.parents[id] != root {
+            let parent = self.parents[id];
+            self.parents[id] = root;
+            id = parent;
+        }
+        root
+    }
+
+    /// Merges the sets containing `a` and `b`, returning false if they were already the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut root_a, mut root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return false;
+        }
+        if self.sizes[root_a] < self.sizes[root_b] {
+            std::mem::swap(&mut root_a, &mut root_b);
+        }
+        self.parents[root_b] = root_a;
+        self.sizes[root_a] += self.sizes[root_b];
+        self.num_sets -= 1;
+        true
+    }
+
+    #[allow(dead_code)]
+    pub fn same_set(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// The number of ids in `id`'s set.
+    #[allow(dead_code)]
+    pub fn set_size(&mut self, id: usize) -> usize {
+        let root = self.find(id);
+        self.sizes[root]
+    }
+
+    /// The sizes of every set, in no particular order.
+    pub fn set_sizes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.parents
+            .iter()
+            .enumerate()
+            .filter(|&(id, &parent)| id == parent)
+            .map(|(root, _)| self.sizes[root])
+    }
+
+    /// Every set, with its ids in increasing order. Sets are ordered by their smallest id.
+    #[allow(dead_code)]
+    pub fn sets(&mut self) -> Vec<Vec<usize>> {
+        let mut set_index_by_root = vec![usize::MAX; self.len()];
+        let mut sets: Vec<Vec<usize>> = Vec::with_capacity(self.num_sets);
+        for id in 0..self.len() {
+            let root = self.find(id);
+            if set_index_by_root[root] == usize::MAX {
+                set_index_by_root[root] = sets.len();
+                sets.push(Vec::with_capacity(self.sizes[root]));
+            }
+            sets[set_index_by_root[root]].push(id);
+        }
+        sets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_apart() {
+        let mut sets = DisjointSets::new(3);
+        assert_eq!(sets.len(), 3);
+        assert_eq!(sets.num_sets(), 3);
+        assert!(!sets.same_set(0, 1));
+        assert_eq!(sets.sets(), vec![vec![0], vec![1], vec![2]]);
+    }
+
+    #[test]
+    fn union_merges_sets() {
+        let mut sets = DisjointSets::new(5);
+        assert!(sets.union(0, 3));
+        assert!(sets.union(4, 3));
+        assert!(!sets.union(0, 4));
+
+        assert_eq!(sets.num_sets(), 3);
+        assert!(sets.same_set(0, 4));
+        assert_eq!(sets.set_size(4), 3);
+        assert_eq!(sets.set_size(1), 1);
+        assert_eq!(sets.sets(), vec![vec![0, 3, 4], vec![1], vec![2]]);
+
+        let mut sizes: Vec<usize> = sets.set_sizes().collect();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![1, 1, 3]);
+    }
+
+    #[test]
+    fn union_by_size_keeps_larger_root() {
+        let mut sets = DisjointSets::new(4);
+        sets.union(1, 2);
+        sets.union(1, 3);
+        let root = sets.find(1);
+        sets.union(0, 1);
+        assert_eq!(sets.find(0), root);
+    }
+
+    #[test]
+    fn find_compresses_paths() {
+        let mut sets = DisjointSets::new(4);
+        // Build a chain by hand, as unions by size never make one.
+        sets.parents = vec![0, 0, 1, 2];
+        sets.sizes = vec![4, 1, 1, 1];
+        sets.num_sets = 1;
+
+        assert_eq!(sets.find(3), 0);
+        assert_eq!(sets.parents, vec![0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn empty() {
+        let mut sets = DisjointSets::new(0);
+        assert!(sets.is_empty());
+        assert_eq!(sets.num_sets(), 0);
+        assert!(sets.sets().is_empty());
+    }
+}
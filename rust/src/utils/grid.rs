@@ -0,0 +1,450 @@
+// A generic 2D grid, for puzzles whose input is rows of characters
+
+use std::fmt::{self, Display};
+
+use crate::utils::input::Error;
+
+/// Offsets to the orthogonal neighbours of a location.
+const ORTHOGONAL_OFFSETS: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+/// Offsets to the orthogonal and diagonal neighbours of a location.
+const SURROUNDING_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// A location in a grid, where `x` is the column and `y` is the row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Location {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Location {
+    pub fn new(x: usize, y: usize) -> Location {
+        Location { x, y }
+    }
+
+    /// Moves by the given offset, or returns `None` if that would leave the top or left edge.
+    pub fn offset(&self, dx: isize, dy: isize) -> Option<Location> {
+        Some(Location::new(
+            self.x.checked_add_signed(dx)?,
+            self.y.checked_add_signed(dy)?,
+        ))
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// A rectangular grid of cells, stored row by row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T> {
+    num_rows: usize,
+    num_cols: usize,
+    data: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Parses one row per line, converting each character with `parse_cell`.
+    ///
+    /// Every line must have the same number of characters. Errors from `parse_cell` are
+    /// positioned at the character that failed.
+    pub fn parse(
+        input: &str,
+        mut parse_cell: impl FnMut(char) -> Result<T, Error>,
+    ) -> Result<Grid<T>, Error> {
+        let mut num_rows = 0;
+        let mut num_cols = 0;
+        let mut data = Vec::new();
+        for (i, line) in input.lines().enumerate() {
+            let line_len = line.chars().count();
+            if i == 0 {
+                num_cols = line_len;
+            } else if line_len != num_cols {
+                return Err(Error::parse_at(
+                    input,
+                    line,
+                    format!(
+                        "inconsistent number of columns; expected {num_cols}, found {line_len}"
+                    ),
+                ));
+            }
+            for (start, c) in line.char_indices() {
+                let cell = &line[start..(start + c.len_utf8())];
+                data.push(parse_cell(c).map_err(|err| err.within(input, cell))?);
+            }
+            num_rows += 1;
+        }
+        Ok(Grid {
+            num_rows,
+            num_cols,
+            data,
+        })
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    fn to_index(&self, location: &Location) -> Option<usize> {
+        if location.x >= self.num_cols || location.y >= self.num_rows {
+            None
+        } else {
+            Some(location.y * self.num_cols + location.x)
+        }
+    }
+
+    pub fn contains(&self, location: &Location) -> bool {
+        self.to_index(location).is_some()
+    }
+
+    pub fn get(&self, location: &Location) -> Option<&T> {
+        self.to_index(location).map(|index| &self.data[index])
+    }
+
+    pub fn get_mut(&mut self, location: &Location) -> Option<&mut T> {
+        self.to_index(location).map(|index| &mut self.data[index])
+    }
+
+    /// Sets the cell at `location`, returning false if it is outside the grid.
+    pub fn set(&mut self, location: &Location, value: T) -> bool {
+        match self.get_mut(location) {
+            Some(cell) => {
+                *cell = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Iterates over every location in the grid, row by row.
+    pub fn locations(&self) -> impl Iterator<Item = Location> + use<T> {
+        let num_cols = self.num_cols;
+        (0..self.num_rows).flat_map(move |y| (0..num_cols).map(move |x| Location::new(x, y)))
+    }
+
+    /// Iterates over every cell in the grid, row by row, along with its location.
+    pub fn iter(&self) -> impl Iterator<Item = (Location, &T)> {
+        self.locations().zip(self.data.iter())
+    }
+
+    /// Returns a grid of the same shape, with `f` applied to every cell.
+    pub fn map<U>(&self, mut f: impl FnMut(Location, &T) -> U) -> Grid<U> {
+        Grid {
+            num_rows: self.num_rows,
+            num_cols: self.num_cols,
+            data: self
+                .iter()
+                .map(|(location, cell)| f(location, cell))
+                .collect(),
+        }
+    }
+
+    fn neighbours_at_offsets(
+        &self,
+        location: Location,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = Location> + '_ {
+        offsets
+            .iter()
+            .filter_map(move |&(dx, dy)| location.offset(dx, dy))
+            .filter(|neighbour| self.contains(neighbour))
+    }
+
+    /// Iterates over the up to 4 orthogonal neighbours of `location` that are inside the grid.
+    #[allow(dead_code)]
+    pub fn orthogonal_neighbours(&self, location: Location) -> impl Iterator<Item = Location> + '_ {
+        self.neighbours_at_offsets(location, &ORTHOGONAL_OFFSETS)
+    }
+
+    /// Iterates over the up to 8 orthogonal and diagonal neighbours of `location` that are inside
+    /// the grid.
+    pub fn surrounding_neighbours(
+        &self,
+        location: Location,
+    ) -> impl Iterator<Item = Location> + '_ {
+        self.neighbours_at_offsets(location, &SURROUNDING_OFFSETS)
+    }
+
+    #[allow(dead_code)]
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        if y >= self.num_rows {
+            return None;
+        }
+        Some(&self.data[(y * self.num_cols)..((y + 1) * self.num_cols)])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on a chunk size of 0, so skip it for grids without columns.
+        self.data
+            .chunks(self.num_cols.max(1))
+            .take(if self.num_cols == 0 { 0 } else { self.num_rows })
+    }
+
+    /// Iterates down the column at `x`, or returns `None` if it is outside the grid.
+    #[allow(dead_code)]
+    pub fn column(&self, x: usize) -> Option<impl Iterator<Item = &T>> {
+        if x >= self.num_cols {
+            return None;
+        }
+        Some(self.data.iter().skip(x).step_by(self.num_cols))
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    #[allow(dead_code)]
+    pub fn new(num_cols: usize, num_rows: usize, value: T) -> Grid<T> {
+        Grid {
+            num_rows,
+            num_cols,
+            data: vec![value; num_rows * num_cols],
+        }
+    }
+
+    /// Returns a copy of the grid with rows and columns swapped.
+    #[allow(dead_code)]
+    pub fn transposed(&self) -> Grid<T> {
+        let mut data = Vec::with_capacity(self.data.len());
+        for x in 0..self.num_cols {
+            data.extend(self.data.iter().skip(x).step_by(self.num_cols).cloned());
+        }
+        Grid {
+            num_rows: self.num_cols,
+            num_cols: self.num_rows,
+            data,
+        }
+    }
+}
+
+/// Writes the grid back out as text, one line per row.
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn parse_chars(input: &str) -> Grid<char> {
+        Grid::parse(input, Ok).unwrap()
+    }
+
+    #[test]
+    fn parse_grid() {
+        let grid = parse_chars(
+            "abc
+def",
+        );
+
+        assert_eq!(grid.num_cols(), 3);
+        assert_eq!(grid.num_rows(), 2);
+        assert_eq!(grid.get(&Location::new(0, 0)), Some(&'a'));
+        assert_eq!(grid.get(&Location::new(2, 1)), Some(&'f'));
+        assert_eq!(grid.get(&Location::new(3, 0)), None);
+        assert_eq!(grid.get(&Location::new(0, 2)), None);
+    }
+
+    #[test]
+    fn parse_grid_mismatched_columns() {
+        let err = Grid::parse("abc\nde", Ok).unwrap_err();
+        assert_eq!(err.position().map(|p| p.line), Some(2));
+    }
+
+    #[test]
+    fn parse_grid_cell_error() {
+        let input = "12\n3x";
+        let err = Grid::parse(input, |c| {
+            c.to_digit(10)
+                .ok_or_else(|| Error::ParseError(format!("not a digit: {}", c)))
+        })
+        .unwrap_err();
+
+        assert_eq!(
+            err.position().map(|p| (p.line, p.column, p.span.clone())),
+            Some((2, 2, 4..5))
+        );
+    }
+
+    #[test]
+    fn set_inside_and_outside() {
+        let mut grid = Grid::new(2, 2, '.');
+
+        assert!(grid.set(&Location::new(1, 0), '#'));
+        assert!(!grid.set(&Location::new(2, 0), '#'));
+        assert_eq!(grid.to_string(), ".#\n..");
+    }
+
+    #[test]
+    fn surrounding_neighbours_all() {
+        let grid = Grid::new(6, 6, '.');
+        let neighbours: HashSet<Location> =
+            grid.surrounding_neighbours(Location::new(3, 3)).collect();
+        assert_eq!(
+            neighbours,
+            [
+                Location::new(2, 2),
+                Location::new(2, 3),
+                Location::new(2, 4),
+                Location::new(3, 2),
+                Location::new(3, 4),
+                Location::new(4, 2),
+                Location::new(4, 3),
+                Location::new(4, 4),
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    #[test]
+    fn surrounding_neighbours_top_left() {
+        let grid = Grid::new(6, 6, '.');
+        let neighbours: HashSet<Location> =
+            grid.surrounding_neighbours(Location::new(0, 0)).collect();
+        assert_eq!(
+            neighbours,
+            [
+                Location::new(0, 1),
+                Location::new(1, 0),
+                Location::new(1, 1),
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    #[test]
+    fn surrounding_neighbours_bottom_right() {
+        let grid = Grid::new(3, 2, '.');
+        let neighbours: HashSet<Location> =
+            grid.surrounding_neighbours(Location::new(2, 1)).collect();
+        assert_eq!(
+            neighbours,
+            [
+                Location::new(1, 0),
+                Location::new(2, 0),
+                Location::new(1, 1),
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    #[test]
+    fn orthogonal_neighbours() {
+        let grid = Grid::new(3, 3, '.');
+        let centre: Vec<Location> = grid.orthogonal_neighbours(Location::new(1, 1)).collect();
+        assert_eq!(
+            centre,
+            vec![
+                Location::new(1, 0),
+                Location::new(0, 1),
+                Location::new(2, 1),
+                Location::new(1, 2),
+            ]
+        );
+
+        let corner: Vec<Location> = grid.orthogonal_neighbours(Location::new(2, 2)).collect();
+        assert_eq!(corner, vec![Location::new(2, 1), Location::new(1, 2)]);
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = parse_chars(
+            "abc
+def",
+        );
+
+        assert_eq!(grid.row(1), Some(&['d', 'e', 'f'][..]));
+        assert_eq!(grid.row(2), None);
+        assert_eq!(
+            grid.rows().collect::<Vec<_>>(),
+            vec![&['a', 'b', 'c'][..], &['d', 'e', 'f'][..]]
+        );
+        assert_eq!(
+            grid.column(1).unwrap().collect::<String>(),
+            "be".to_string()
+        );
+        assert!(grid.column(3).is_none());
+    }
+
+    #[test]
+    fn transposed() {
+        let grid = parse_chars(
+            "abc
+def",
+        );
+
+        let transposed = grid.transposed();
+
+        assert_eq!(transposed.num_cols(), 2);
+        assert_eq!(transposed.num_rows(), 3);
+        assert_eq!(transposed.to_string(), "ad\nbe\ncf");
+        assert_eq!(transposed.transposed(), grid);
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let input = "..@\n@@.\n.@.";
+        assert_eq!(parse_chars(input).to_string(), input);
+    }
+
+    #[test]
+    fn iter_visits_row_by_row() {
+        let grid = parse_chars("ab\ncd");
+        let cells: Vec<(Location, char)> = grid.iter().map(|(l, &c)| (l, c)).collect();
+        assert_eq!(
+            cells,
+            vec![
+                (Location::new(0, 0), 'a'),
+                (Location::new(1, 0), 'b'),
+                (Location::new(0, 1), 'c'),
+                (Location::new(1, 1), 'd'),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_grid() {
+        let grid = parse_chars("");
+        assert_eq!(grid.num_rows(), 0);
+        assert_eq!(grid.rows().count(), 0);
+        assert_eq!(grid.to_string(), "");
+    }
+
+    #[test]
+    fn map_keeps_shape() {
+        let grid = parse_chars("ab\ncd");
+        let mapped = grid.map(|location, &c| format!("{}{}", c, location.x + location.y));
+        assert_eq!(mapped.num_cols(), 2);
+        assert_eq!(
+            mapped.row(1),
+            Some(&["c1".to_string(), "d2".to_string()][..])
+        );
+    }
+}
@@ -1,12 +1,595 @@
 // Input parsing utilities for Advent of Code solutions
 // Add parsing helpers as needed when implementing days
 
+use std::fmt::{self, Display};
+use std::ops::{Range, RangeInclusive};
+use std::str::FromStr;
+
 use thiserror::Error as ThisError;
 
-#[derive(Debug, Clone, ThisError)]
+use crate::utils::grid::Grid;
+
+/// Where in the input a parse error happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    /// The line, counting from 1.
+    pub line: usize,
+    /// The column, counting characters from 1.
+    pub column: usize,
+    /// The byte range of the offending text within the input.
+    pub span: Range<usize>,
+}
+
+impl Position {
+    /// Finds the line and column at which `span`, a byte range within `input`, starts.
+    pub fn of_span(input: &str, span: Range<usize>) -> Position {
+        let before = &input[..span.start];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Position {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            span,
+        }
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+// The variants are named for what went wrong, and are used as is across every day.
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, PartialEq, ThisError)]
 pub enum Error {
     #[error("parse error: {0}")]
     ParseError(String),
+    #[error("parse error at {position}: {message}")]
+    PositionedParseError { message: String, position: Position },
     #[error("logic error: {0}")]
     LogicError(String),
 }
+
+/// Columns between tab stops, used when expanding tabs in significant whitespace.
+const TAB_WIDTH: usize = 8;
+
+/// Whether the spaces and tabs in a day's input carry meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Whitespace {
+    /// Trailing spaces and tabs are dropped from every line.
+    Insignificant,
+    /// Spaces are kept, for inputs whose columns line up across lines. Tabs are expanded to
+    /// spaces so that the columns still line up.
+    Significant,
+}
+
+/// Cleans up input saved by different editors and platforms, so that parsers only need to handle
+/// `\n` line endings.
+///
+/// A UTF-8 byte order mark, `\r` before line endings and trailing blank lines are always removed.
+/// A line of only spaces or tabs counts as blank, even when whitespace is significant.
+pub fn normalize(input: &str, whitespace: Whitespace) -> String {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut lines: Vec<String> = input
+        .split('\n')
+        .map(|line| {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            match whitespace {
+                Whitespace::Insignificant => line.trim_end_matches([' ', '\t']).to_string(),
+                Whitespace::Significant => expand_tabs(line),
+            }
+        })
+        .collect();
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+fn expand_tabs(line: &str) -> String {
+    let mut expanded = String::with_capacity(line.len());
+    let mut column = 0;
+    for c in line.chars() {
+        if c == '\t' {
+            let num_spaces = TAB_WIDTH - column % TAB_WIDTH;
+            expanded.extend(std::iter::repeat_n(' ', num_spaces));
+            column += num_spaces;
+        } else {
+            expanded.push(c);
+            column += 1;
+        }
+    }
+    expanded
+}
+
+/// Returns the byte range of `fragment` within `input`, which it must be a slice of.
+pub fn span_of(input: &str, fragment: &str) -> Range<usize> {
+    let start = (fragment.as_ptr() as usize).wrapping_sub(input.as_ptr() as usize);
+    assert!(
+        start <= input.len() && fragment.len() <= input.len() - start,
+        "fragment is not a slice of the input"
+    );
+    start..(start + fragment.len())
+}
+
+impl Error {
+    /// A parse error about `fragment`, which must be a slice of `input`.
+    pub fn parse_at(input: &str, fragment: &str, message: impl Into<String>) -> Error {
+        Error::PositionedParseError {
+            message: message.into(),
+            position: Position::of_span(input, span_of(input, fragment)),
+        }
+    }
+
+    /// Places an error from parsing `fragment`, a slice of `input`, within the whole of `input`.
+    ///
+    /// Positions are moved to the same text in `input`, and parse errors without a position are
+    /// given the whole fragment.
+    pub fn within(self, input: &str, fragment: &str) -> Error {
+        match self {
+            Error::ParseError(message) => Error::parse_at(input, fragment, message),
+            Error::PositionedParseError { message, position } => {
+                let offset = span_of(input, fragment).start;
+                let span = (position.span.start + offset)..(position.span.end + offset);
+                Error::PositionedParseError {
+                    message,
+                    position: Position::of_span(input, span),
+                }
+            }
+            Error::LogicError(_) => self,
+        }
+    }
+
+    /// Places an error from parsing `line` on its own at `line_number` of an input that is read a
+    /// line at a time. Like [`Error::within`], parse errors without a position are given the whole
+    /// line. The span still refers to `line`, since the rest of the input is not kept.
+    pub fn on_line(self, line: &str, line_number: usize) -> Error {
+        match self.within(line, line) {
+            Error::PositionedParseError {
+                message,
+                mut position,
+            } => {
+                position.line += line_number - 1;
+                Error::PositionedParseError { message, position }
+            }
+            err => err,
+        }
+    }
+
+    pub fn position(&self) -> Option<&Position> {
+        match self {
+            Error::PositionedParseError { position, .. } => Some(position),
+            _ => None,
+        }
+    }
+
+    /// Renders the error like a compiler diagnostic, quoting the offending line of `input` with a
+    /// caret under the problem.
+    pub fn render(&self, input: &str) -> String {
+        let (message, position) = match self {
+            Error::PositionedParseError { message, position } => (message, position),
+            _ => return format!("{}\n", self),
+        };
+        let line = input
+            .lines()
+            .nth(position.line - 1)
+            .unwrap_or("")
+            .trim_end_matches('\r');
+        // Keep tabs so that the caret lines up however wide the terminal draws them.
+        let indent: String = line
+            .chars()
+            .take(position.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let width = input
+            .get(position.span.clone())
+            .unwrap_or("")
+            .chars()
+            .take_while(|&c| c != '\n')
+            .count()
+            .max(1);
+        let line_number = position.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        format!(
+            "parse error: {message}\n\
+             {gutter}--> {position}\n\
+             {gutter} |\n\
+             {line_number} | {line}\n\
+             {gutter} | {indent}{carets}\n",
+            carets = "^".repeat(width),
+        )
+    }
+}
+
+/// Parses a number, ignoring surrounding spaces. Errors are positioned at `text`.
+pub fn parse_number<T>(text: &str) -> Result<T, Error>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let number = text.trim();
+    number
+        .parse()
+        .map_err(|err| Error::parse_at(text, number, format!("invalid number {}: {}", number, err)))
+}
+
+/// Parses every item of a `delimiter`-separated list with `parse_item`, e.g. `1,2,3`.
+pub fn parse_list_with<T>(
+    text: &str,
+    delimiter: char,
+    mut parse_item: impl FnMut(&str) -> Result<T, Error>,
+) -> Result<Vec<T>, Error> {
+    text.split(delimiter)
+        .map(|item| parse_item(item).map_err(|err| err.within(text, item)))
+        .collect()
+}
+
+/// Parses exactly `N` items separated by `delimiter` with `parse_item`, e.g. `x,y,z`.
+pub fn parse_tuple_with<T, const N: usize>(
+    text: &str,
+    delimiter: char,
+    parse_item: impl FnMut(&str) -> Result<T, Error>,
+) -> Result<[T; N], Error> {
+    let num_items = text.split(delimiter).count();
+    if num_items != N {
+        return Err(Error::parse_at(
+            text,
+            text,
+            format!(
+                "expected {} values separated by '{}', found {}",
+                N, delimiter, num_items
+            ),
+        ));
+    }
+    let items = parse_list_with(text, delimiter, parse_item)?;
+    Ok(items
+        .try_into()
+        .unwrap_or_else(|_| unreachable!("the number of items was checked")))
+}
+
+/// Parses exactly `N` numbers separated by `delimiter`, e.g. `x,y,z`.
+pub fn parse_tuple<T, const N: usize>(text: &str, delimiter: char) -> Result<[T; N], Error>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    parse_tuple_with(text, delimiter, parse_number)
+}
+
+/// Parses an inclusive range written as `start-end`. The range may be empty.
+pub fn parse_range<T>(text: &str) -> Result<RangeInclusive<T>, Error>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let [start, end] = parse_tuple(text, '-')?;
+    Ok(start..=end)
+}
+
+/// Splits `input` into exactly `N` sections separated by blank lines.
+pub fn parse_sections<const N: usize>(input: &str) -> Result<[&str; N], Error> {
+    let sections: Vec<&str> = input.split("\n\n").collect();
+    if sections.len() > N {
+        return Err(Error::parse_at(
+            input,
+            sections[N],
+            format!("expected only {} sections separated by blank lines", N),
+        ));
+    }
+    // Not positioned: there is no one place where the missing blank line should have been.
+    sections.try_into().map_err(|sections: Vec<&str>| {
+        Error::ParseError(format!(
+            "expected {} sections separated by blank lines, found {}",
+            N,
+            sections.len()
+        ))
+    })
+}
+
+/// Parses every line of `input` with `parse_line`, positioning errors within `input`.
+pub fn parse_lines<T>(
+    input: &str,
+    mut parse_line: impl FnMut(&str) -> Result<T, Error>,
+) -> Result<Vec<T>, Error> {
+    input
+        .lines()
+        .map(|line| parse_line(line).map_err(|err| err.within(input, line)))
+        .collect()
+}
+
+/// Parses a grid of characters into cells of a type that converts from `char`.
+pub fn parse_grid<T>(input: &str) -> Result<Grid<T>, Error>
+where
+    T: TryFrom<char, Error = Error>,
+{
+    Grid::parse(input, T::try_from)
+}
+
+/// The line and column of the error in `result`, if it failed with a positioned error.
+#[cfg(test)]
+pub fn error_position<T>(result: Result<T, Error>) -> Option<(usize, usize)> {
+    result.err()?.position().map(|p| (p.line, p.column))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_of_span() {
+        let input = "ab\ncdé\nfg";
+
+        assert_eq!(
+            Position::of_span(input, 0..1),
+            Position {
+                line: 1,
+                column: 1,
+                span: 0..1
+            }
+        );
+        assert_eq!(Position::of_span(input, 4..5).column, 2);
+        // "é" is two bytes, so "f" starts at byte 8 on line 3.
+        assert_eq!(
+            Position::of_span(input, 8..9),
+            Position {
+                line: 3,
+                column: 1,
+                span: 8..9
+            }
+        );
+    }
+
+    #[test]
+    fn within_moves_position_into_input() {
+        let input = "1-2\n3-x";
+        let line = input.lines().nth(1).unwrap();
+        let err = Error::parse_at(line, &line[2..], "bad end").within(input, line);
+
+        assert_eq!(
+            err.position(),
+            Some(&Position {
+                line: 2,
+                column: 3,
+                span: 6..7
+            })
+        );
+    }
+
+    #[test]
+    fn within_positions_unpositioned_errors() {
+        let input = "ab\ncd";
+        let err = Error::ParseError("bad".to_string()).within(input, &input[3..4]);
+
+        assert_eq!(err.position().map(|p| (p.line, p.column)), Some((2, 1)));
+        assert!(
+            Error::LogicError("oops".to_string())
+                .within(input, &input[3..4])
+                .position()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn on_line_moves_position_to_line() {
+        let line = "3-x";
+        let err = Error::parse_at(line, &line[2..], "bad end").on_line(line, 7);
+        assert_eq!(
+            err.position(),
+            Some(&Position {
+                line: 7,
+                column: 3,
+                span: 2..3
+            })
+        );
+
+        let err = Error::ParseError("bad".to_string()).on_line(line, 2);
+        assert_eq!(err.position().map(|p| (p.line, p.column)), Some((2, 1)));
+    }
+
+    #[test]
+    fn render_quotes_line_with_carets() {
+        let input = "3-5\n10-1x\n16-20";
+        let line = input.lines().nth(1).unwrap();
+        let err = Error::parse_at(input, &line[3..5], "invalid end");
+
+        assert_eq!(
+            err.to_string(),
+            "parse error at line 2, column 4: invalid end"
+        );
+        assert_eq!(
+            err.render(input),
+            "parse error: invalid end
+ --> line 2, column 4
+  |
+2 | 10-1x
+  |    ^^
+"
+        );
+    }
+
+    #[test]
+    fn normalize_line_endings_and_bom() {
+        assert_eq!(
+            normalize(
+                "\u{feff}3-5\r\n10-14\r\n\r\n1\r\n",
+                Whitespace::Insignificant
+            ),
+            "3-5\n10-14\n\n1"
+        );
+    }
+
+    #[test]
+    fn normalize_trailing_blank_lines() {
+        assert_eq!(
+            normalize("a\nb\n\n \t\n", Whitespace::Insignificant),
+            "a\nb"
+        );
+        assert_eq!(normalize("\n\n", Whitespace::Insignificant), "");
+        assert_eq!(normalize("a\n\nb", Whitespace::Insignificant), "a\n\nb");
+    }
+
+    #[test]
+    fn normalize_trailing_blank_lines_with_significant_whitespace() {
+        assert_eq!(
+            normalize(
+                "12 
+*  
+   
+	
+",
+                Whitespace::Significant
+            ),
+            "12 
+*  "
+        );
+        assert_eq!(
+            normalize(
+                "  
+ 
+",
+                Whitespace::Significant
+            ),
+            ""
+        );
+        assert_eq!(
+            normalize(
+                "1
+   
+2",
+                Whitespace::Significant
+            ),
+            "1
+   
+2"
+        );
+    }
+
+    #[test]
+    fn normalize_insignificant_whitespace() {
+        assert_eq!(
+            normalize("1,2 \t\n3,4\t", Whitespace::Insignificant),
+            "1,2\n3,4"
+        );
+    }
+
+    #[test]
+    fn normalize_significant_whitespace() {
+        assert_eq!(
+            normalize("12 \r\n*  \r\n", Whitespace::Significant),
+            "12 \n*  "
+        );
+        assert_eq!(
+            normalize("1\t2\n123456789\t3", Whitespace::Significant),
+            "1       2\n123456789       3"
+        );
+    }
+
+    #[test]
+    fn render_without_position() {
+        let err = Error::ParseError("bad".to_string());
+        assert_eq!(err.render("anything"), "parse error: bad\n");
+    }
+
+    #[test]
+    fn parse_number_positions_error() {
+        assert_eq!(parse_number::<u32>(" 42"), Ok(42));
+
+        let err = parse_number::<u32>(" 4x").unwrap_err();
+        assert_eq!(err.position().map(|p| p.span.clone()), Some(1..3));
+    }
+
+    #[test]
+    fn parse_list_of_numbers() {
+        assert_eq!(
+            parse_list_with("1,-2, 3", ',', parse_number::<i32>),
+            Ok(vec![1, -2, 3])
+        );
+
+        let err = parse_list_with("1,2,x", ',', parse_number::<i32>).unwrap_err();
+        assert_eq!(err.position().map(|p| p.span.clone()), Some(4..5));
+    }
+
+    #[test]
+    fn parse_tuple_checks_arity() {
+        assert_eq!(parse_tuple::<u8, 3>("1,2,3", ','), Ok([1, 2, 3]));
+
+        let err = parse_tuple::<u8, 3>("1,2", ',').unwrap_err();
+        assert_eq!(err.position().map(|p| p.span.clone()), Some(0..3));
+        assert!(err.to_string().contains("expected 3 values"));
+    }
+
+    #[test]
+    fn parse_tuple_with_custom_items() {
+        let parse_small = |text: &str| match parse_number(text)? {
+            value if value < 10 => Ok(value),
+            _ => Err(Error::parse_at(text, text, "too big")),
+        };
+        assert_eq!(
+            parse_tuple_with::<u8, 2>("1,9", ',', parse_small),
+            Ok([1, 9])
+        );
+
+        let err = parse_tuple_with::<u8, 2>("1,10", ',', parse_small).unwrap_err();
+        assert_eq!(err.position().map(|p| p.span.clone()), Some(2..4));
+    }
+
+    #[test]
+    fn parse_range_of_numbers() {
+        assert_eq!(parse_range::<u64>("3-5"), Ok(3..=5));
+        assert!(parse_range::<u64>("5-3").is_ok_and(|range| range.is_empty()));
+
+        let err = parse_range::<u64>("10-1x").unwrap_err();
+        assert_eq!(err.position().map(|p| p.span.clone()), Some(3..5));
+        assert!(parse_range::<u64>("1-2-3").is_err());
+    }
+
+    #[test]
+    fn parse_sections_by_blank_lines() {
+        assert_eq!(parse_sections("a\nb\n\nc"), Ok(["a\nb", "c"]));
+
+        let err = parse_sections::<2>("a\nb").unwrap_err();
+        assert!(err.position().is_none());
+
+        let err = parse_sections::<2>("a\n\nb\n\nc").unwrap_err();
+        assert_eq!(err.position().map(|p| p.line), Some(5));
+    }
+
+    #[test]
+    fn parse_lines_positions_errors() {
+        let parse_pair = |line: &str| parse_tuple::<u8, 2>(line, ',');
+        assert_eq!(
+            parse_lines("1,2\n3,4", parse_pair),
+            Ok(vec![[1, 2], [3, 4]])
+        );
+
+        assert_eq!(
+            error_position(parse_lines("1,2\n3,4\n5,x", parse_pair)),
+            Some((3, 3))
+        );
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Wall(bool);
+
+    impl TryFrom<char> for Wall {
+        type Error = Error;
+
+        fn try_from(c: char) -> Result<Wall, Error> {
+            match c {
+                '#' => Ok(Wall(true)),
+                '.' => Ok(Wall(false)),
+                _ => Err(Error::ParseError(format!("unknown cell {}", c))),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_typed_grid() {
+        let grid: Grid<Wall> = parse_grid("#.\n.#").unwrap();
+        assert_eq!(grid.num_cols(), 2);
+        assert_eq!(grid.iter().filter(|(_, wall)| wall.0).count(), 2);
+
+        assert_eq!(error_position(parse_grid::<Wall>("#.\n.x")), Some((2, 2)));
+    }
+}
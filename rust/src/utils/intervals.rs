@@ -0,0 +1,382 @@
+// Sets of integers stored as sorted, disjoint ranges
+
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+
+/// An integer type that an [`IntervalSet`] can hold.
+pub trait Integer: Copy + Ord + Debug {
+    const MIN: Self;
+    const MAX: Self;
+
+    /// The next integer up, or None for `MAX`.
+    fn successor(self) -> Option<Self>;
+
+    /// The next integer down, or None for `MIN`.
+    fn predecessor(self) -> Option<Self>;
+
+    /// How many integers are in `start..=self`, which must not be empty.
+    fn span_from(self, start: Self) -> u128;
+}
+
+macro_rules! integers {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl Integer for $t {
+                const MIN: $t = <$t>::MIN;
+                const MAX: $t = <$t>::MAX;
+
+                fn successor(self) -> Option<$t> {
+                    self.checked_add(1)
+                }
+
+                fn predecessor(self) -> Option<$t> {
+                    self.checked_sub(1)
+                }
+
+                fn span_from(self, start: $t) -> u128 {
+                    (self as i128 - start as i128) as u128 + 1
+                }
+            }
+        )*
+    };
+}
+
+integers!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// A set of integers, held as inclusive ranges that are sorted, and neither overlap nor touch.
+///
+/// Building a set sorts its ranges once and sweeps over them, merging any that overlap or are
+/// adjacent, like `1..=3` and `4..=5`. Membership is a binary search over the ranges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    ranges: Vec<RangeInclusive<T>>,
+}
+
+impl<T: Integer> Default for IntervalSet<T> {
+    fn default() -> IntervalSet<T> {
+        IntervalSet::new()
+    }
+}
+
+impl<T: Integer> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet { ranges: Vec::new() }
+    }
+
+    /// Every integer in `range`.
+    #[allow(dead_code)]
+    pub fn from_range(range: RangeInclusive<T>) -> IntervalSet<T> {
+        IntervalSet::from_ranges([range])
+    }
+
+    /// Every integer in any of `ranges`, which may be in any order and may overlap. Empty ranges
+    /// add nothing.
+    pub fn from_ranges(ranges: impl IntoIterator<Item = RangeInclusive<T>>) -> IntervalSet<T> {
+        let mut ranges: Vec<RangeInclusive<T>> =
+            ranges.into_iter().filter(|r| !r.is_empty()).collect();
+        ranges.sort_unstable_by_key(|r| *r.start());
+        IntervalSet::from_sorted(ranges)
+    }
+
+    /// Merges ranges that are sorted by their starts and not empty.
+    fn from_sorted(sorted: impl IntoIterator<Item = RangeInclusive<T>>) -> IntervalSet<T> {
+        let mut ranges: Vec<RangeInclusive<T>> = Vec::new();
+        for range in sorted {
+            if let Some(last) = ranges.last_mut()
+                && last
+                    .end()
+                    .successor()
+                    .is_none_or(|next| *range.start() <= next)
+            {
+                if range.end() > last.end() {
+                    *last = *last.start()..=*range.end();
+                }
+                continue;
+            }
+            ranges.push(range);
+        }
+        IntervalSet { ranges }
+    }
+
+    /// The merged ranges, in increasing order.
+    #[allow(dead_code)]
+    pub fn ranges(&self) -> &[RangeInclusive<T>] {
+        &self.ranges
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// How many integers are in the set. This is a `u128` so that every `u64` can be counted.
+    pub fn num_values(&self) -> u128 {
+        self.ranges
+            .iter()
+            .map(|r| r.end().span_from(*r.start()))
+            .sum()
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let i = self.ranges.partition_point(|r| *r.end() < value);
+        self.ranges.get(i).is_some_and(|r| *r.start() <= value)
+    }
+
+    /// Every integer in either set.
+    #[allow(dead_code)]
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        // Both halves are already sorted, which the sort merges in linear time.
+        let mut ranges: Vec<RangeInclusive<T>> =
+            self.ranges.iter().chain(&other.ranges).cloned().collect();
+        ranges.sort_by_key(|r| *r.start());
+        IntervalSet::from_sorted(ranges)
+    }
+
+    /// Every integer in both sets.
+    #[allow(dead_code)]
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while let (Some(a), Some(b)) = (self.ranges.get(i), other.ranges.get(j)) {
+            let start = *a.start().max(b.start());
+            let end = *a.end().min(b.end());
+            if start <= end {
+                ranges.push(start..=end);
+            }
+            // Whichever range ends first cannot overlap anything further on in the other set.
+            if a.end() < b.end() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    /// Every integer in this set but not in `other`.
+    #[allow(dead_code)]
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        self.intersection(&other.complement(T::MIN..=T::MAX))
+    }
+
+    /// Every integer in `bounds` that is not in the set.
+    #[allow(dead_code)]
+    pub fn complement(&self, bounds: RangeInclusive<T>) -> IntervalSet<T> {
+        let mut ranges = Vec::new();
+        if bounds.is_empty() {
+            return IntervalSet { ranges };
+        }
+        // The first integer that could start a gap, or None once the gaps pass T::MAX.
+        let mut gap_start = Some(*bounds.start());
+        for range in &self.ranges {
+            let Some(start) = gap_start else {
+                break;
+            };
+            if range.start() > bounds.end() {
+                break;
+            }
+            if let Some(end) = range.start().predecessor()
+                && start <= end
+            {
+                ranges.push(start..=end);
+            }
+            gap_start = gap_start.max(range.end().successor());
+            if range.end() >= bounds.end() {
+                gap_start = None;
+            }
+        }
+        if let Some(start) = gap_start
+            && start <= *bounds.end()
+        {
+            ranges.push(start..=*bounds.end());
+        }
+        IntervalSet { ranges }
+    }
+}
+
+impl<T: Integer> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(ranges: I) -> IntervalSet<T> {
+        IntervalSet::from_ranges(ranges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn set(ranges: &[(u64, u64)]) -> IntervalSet<u64> {
+        ranges.iter().map(|&(start, end)| start..=end).collect()
+    }
+
+    fn pairs(set: &IntervalSet<u64>) -> Vec<(u64, u64)> {
+        set.ranges()
+            .iter()
+            .map(|r| (*r.start(), *r.end()))
+            .collect()
+    }
+
+    #[test]
+    fn from_ranges_merges_overlapping_ranges() {
+        assert_eq!(pairs(&set(&[(4, 7), (3, 8)])), vec![(3, 8)]);
+        assert_eq!(pairs(&set(&[(3, 8), (4, 7)])), vec![(3, 8)]);
+        assert_eq!(pairs(&set(&[(3, 8), (2, 3)])), vec![(2, 8)]);
+        assert_eq!(pairs(&set(&[(5, 8), (8, 9)])), vec![(5, 9)]);
+        assert_eq!(pairs(&set(&[(1, 4), (1, 4)])), vec![(1, 4)]);
+        assert_eq!(
+            pairs(&set(&[(13, 15), (6, 9), (1, 1), (3, 7), (20, 20)])),
+            vec![(1, 1), (3, 9), (13, 15), (20, 20)]
+        );
+    }
+
+    #[test]
+    fn from_ranges_merges_adjacent_ranges() {
+        assert_eq!(pairs(&set(&[(4, 5), (1, 3)])), vec![(1, 5)]);
+        assert_eq!(pairs(&set(&[(1, 3), (5, 6)])), vec![(1, 3), (5, 6)]);
+        assert_eq!(
+            pairs(&set(&[(0, u64::MAX - 1), (u64::MAX, u64::MAX)])),
+            vec![(0, u64::MAX)]
+        );
+    }
+
+    #[test]
+    fn from_ranges_skips_empty_ranges() {
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = 5..=3;
+        assert!(IntervalSet::from_range(empty).is_empty());
+        assert_eq!(IntervalSet::<u64>::default(), IntervalSet::new());
+    }
+
+    #[test]
+    fn contains() {
+        let ranges = set(&[(1, 7), (9, 10), (12, 15)]);
+        for value in [1, 3, 7, 9, 10, 12, 15] {
+            assert!(ranges.contains(value), "{}", value);
+        }
+        for value in [0, 8, 11, 16, u64::MAX] {
+            assert!(!ranges.contains(value), "{}", value);
+        }
+        assert!(!IntervalSet::new().contains(0u64));
+    }
+
+    #[test]
+    fn num_values() {
+        assert_eq!(set(&[(3, 5), (10, 20)]).num_values(), 14);
+        assert_eq!(set(&[(0, u64::MAX)]).num_values(), 1 << 64);
+        assert_eq!(
+            IntervalSet::from_range(i64::MIN..=i64::MAX).num_values(),
+            1 << 64
+        );
+        assert_eq!(IntervalSet::<u64>::new().num_values(), 0);
+    }
+
+    #[test]
+    fn union() {
+        let a = set(&[(1, 3), (10, 12)]);
+        let b = set(&[(4, 5), (8, 9), (20, 21)]);
+        assert_eq!(pairs(&a.union(&b)), vec![(1, 5), (8, 12), (20, 21)]);
+        assert_eq!(a.union(&IntervalSet::new()), a);
+    }
+
+    #[test]
+    fn intersection() {
+        let a = set(&[(1, 5), (8, 12), (20, 30)]);
+        let b = set(&[(3, 9), (12, 25)]);
+        assert_eq!(
+            pairs(&a.intersection(&b)),
+            vec![(3, 5), (8, 9), (12, 12), (20, 25)]
+        );
+        assert!(a.intersection(&IntervalSet::new()).is_empty());
+    }
+
+    #[test]
+    fn difference() {
+        let a = set(&[(1, 10), (20, 30)]);
+        let b = set(&[(0, 2), (5, 6), (10, 21), (30, 40)]);
+        assert_eq!(pairs(&a.difference(&b)), vec![(3, 4), (7, 9), (22, 29)]);
+        assert!(a.difference(&a).is_empty());
+    }
+
+    #[test]
+    fn complement() {
+        let a = set(&[(3, 5), (8, 9)]);
+        assert_eq!(pairs(&a.complement(0..=20)), vec![(0, 2), (6, 7), (10, 20)]);
+        assert_eq!(pairs(&a.complement(4..=8)), vec![(6, 7)]);
+        assert_eq!(pairs(&a.complement(3..=5)), vec![]);
+        assert_eq!(
+            pairs(&set(&[(0, 0), (u64::MAX, u64::MAX)]).complement(0..=u64::MAX)),
+            vec![(1, u64::MAX - 1)]
+        );
+        assert_eq!(
+            pairs(&IntervalSet::new().complement(0..=u64::MAX)),
+            vec![(0, u64::MAX)]
+        );
+    }
+
+    /// Ranges over a small domain, so that sets can be checked against every value in it. Some
+    /// are empty, being inverted.
+    fn small_ranges() -> impl Strategy<Value = Vec<RangeInclusive<u8>>> {
+        prop::collection::vec((0u8..60, 0u8..60).prop_map(|(a, b)| a..=b), 0..8)
+    }
+
+    /// The values in any of `ranges`, found by checking every `u8` against each range.
+    fn naive(ranges: &[RangeInclusive<u8>]) -> Vec<u8> {
+        (0..=u8::MAX)
+            .filter(|value| ranges.iter().any(|r| r.contains(value)))
+            .collect()
+    }
+
+    fn values(set: &IntervalSet<u8>) -> Vec<u8> {
+        set.ranges().iter().flat_map(|r| r.clone()).collect()
+    }
+
+    proptest! {
+        #[test]
+        fn from_ranges_is_sorted_and_disjoint(ranges in small_ranges()) {
+            let set = IntervalSet::from_ranges(ranges);
+            for range in set.ranges() {
+                prop_assert!(!range.is_empty(), "{:?}", set);
+            }
+            for pair in set.ranges().windows(2) {
+                // Not even adjacent, or they would have been merged.
+                prop_assert!(*pair[0].end() as u16 + 1 < *pair[1].start() as u16, "{:?}", set);
+            }
+        }
+
+        #[test]
+        fn membership_matches_naive_scan(ranges in small_ranges()) {
+            let set = IntervalSet::from_ranges(ranges.clone());
+            let expected = naive(&ranges);
+            for value in 0..=u8::MAX {
+                prop_assert_eq!(set.contains(value), expected.contains(&value), "{}", value);
+            }
+            prop_assert_eq!(values(&set), expected.clone());
+            prop_assert_eq!(set.num_values(), expected.len() as u128);
+        }
+
+        #[test]
+        fn set_operations_match_naive_scan(
+            a in small_ranges(),
+            b in small_ranges(),
+            (start, end) in (0u8..70, 0u8..70),
+        ) {
+            let set_a = IntervalSet::from_ranges(a.clone());
+            let set_b = IntervalSet::from_ranges(b.clone());
+            let (in_a, in_b) = (naive(&a), naive(&b));
+
+            let union: Vec<u8> = (0..=u8::MAX)
+                .filter(|v| in_a.contains(v) || in_b.contains(v))
+                .collect();
+            prop_assert_eq!(values(&set_a.union(&set_b)), union);
+            let intersection: Vec<u8> =
+                in_a.iter().copied().filter(|v| in_b.contains(v)).collect();
+            prop_assert_eq!(values(&set_a.intersection(&set_b)), intersection);
+            let difference: Vec<u8> =
+                in_a.iter().copied().filter(|v| !in_b.contains(v)).collect();
+            prop_assert_eq!(values(&set_a.difference(&set_b)), difference);
+            let complement: Vec<u8> = (start..=end).filter(|v| !in_a.contains(v)).collect();
+            prop_assert_eq!(values(&set_a.complement(start..=end)), complement);
+        }
+    }
+}
@@ -1,3 +1,7 @@
 // Utility modules for Advent of Code solutions
 
+pub mod dsu;
+pub mod grid;
 pub mod input;
+pub mod intervals;
+pub mod spatial;
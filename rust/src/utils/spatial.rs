@@ -0,0 +1,304 @@
+// Spatial indexes over 3D points, for finding close pairs without comparing every pair
+
+use std::collections::HashMap;
+
+use nalgebra::Point3;
+
+type Point = Point3<i64>;
+
+/// Two points, by their index, and the square of the distance between them. `id_a` is always
+/// less than `id_b`.
+///
+/// Squared distances between integer points are exact, so pairs compare the same on every
+/// platform. They are ordered by distance, then by their ids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PointPair {
+    pub squared_distance: i128,
+    pub id_a: usize,
+    pub id_b: usize,
+}
+
+/// The exact square of the distance between `a` and `b`.
+pub fn squared_distance(a: &Point, b: &Point) -> i128 {
+    (0..3)
+        .map(|i| {
+            let delta = i128::from(a[i]) - i128::from(b[i]);
+            delta * delta
+        })
+        .sum()
+}
+
+/// The smallest integer distance that is at least the square root of `squared`.
+fn ceil_sqrt(squared: i128) -> i64 {
+    let root = squared.isqrt();
+    let root = if root * root < squared {
+        root + 1
+    } else {
+        root
+    };
+    i64::try_from(root).unwrap_or(i64::MAX)
+}
+
+/// Points bucketed into cubes, so that the points near a point are found by only looking in its
+/// own cube and the 26 around it.
+pub struct BucketGrid<'a> {
+    points: &'a [Point],
+    cell_size: i64,
+    origin: Point,
+    buckets: HashMap<[i64; 3], Vec<usize>>,
+}
+
+impl<'a> BucketGrid<'a> {
+    /// Buckets `points` into cubes with sides of `cell_size`, which must be positive.
+    pub fn new(points: &'a [Point], cell_size: i64) -> BucketGrid<'a> {
+        assert!(cell_size > 0, "cells must have a positive size");
+        let origin = points
+            .iter()
+            .fold(Point::new(i64::MAX, i64::MAX, i64::MAX), |min, p| {
+                min.inf(p)
+            });
+        let mut grid = BucketGrid {
+            points,
+            cell_size,
+            origin,
+            buckets: HashMap::new(),
+        };
+        for (id, p) in points.iter().enumerate() {
+            let cell = grid.cell_of(p);
+            grid.buckets.entry(cell).or_default().push(id);
+        }
+        grid
+    }
+
+    fn cell_of(&self, p: &Point) -> [i64; 3] {
+        // Points are never below the origin, so the offsets are never negative.
+        [0, 1, 2].map(|i| {
+            let offset =
+                (i128::from(p[i]) - i128::from(self.origin[i])) / i128::from(self.cell_size);
+            offset as i64
+        })
+    }
+
+    /// Every pair whose distance is above `above` (if given) and at most `max_distance`, in no
+    /// particular order. `max_distance` must be no more than the cell size.
+    pub fn pairs_within(&self, above: Option<i64>, max_distance: i64) -> Vec<PointPair> {
+        assert!(
+            max_distance <= self.cell_size,
+            "pairs further apart than a cell may be in cubes that are not neighbours"
+        );
+        let max_squared = i128::from(max_distance).pow(2);
+        let above_squared = above.map(|above| i128::from(above).pow(2));
+        let mut pairs = Vec::new();
+        for (id_a, a) in self.points.iter().enumerate() {
+            let [x, y, z] = self.cell_of(a);
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let Some(bucket) = self.buckets.get(&[x + dx, y + dy, z + dz]) else {
+                            continue;
+                        };
+                        for &id_b in bucket.iter().filter(|&&id_b| id_b > id_a) {
+                            let squared_distance = squared_distance(a, &self.points[id_b]);
+                            if squared_distance <= max_squared
+                                && above_squared.is_none_or(|min| squared_distance > min)
+                            {
+                                pairs.push(PointPair {
+                                    squared_distance,
+                                    id_a,
+                                    id_b,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        pairs
+    }
+}
+
+/// Every pair of points, from closest to furthest, found a band of distances at a time.
+///
+/// Each band is sized to hold about as many pairs as there are points, or a quarter of the pairs
+/// found so far if that is more. Taking the closest few pairs of a large input never holds all
+/// `n * (n - 1) / 2` of them, and taking every pair only searches a few dozen bands. Pairs at the
+/// same distance are ordered by their ids.
+pub struct PairsByDistance<'a> {
+    points: &'a [Point],
+    /// The distance up to which pairs have been found.
+    radius: Option<i64>,
+    /// The furthest any two points can be apart, rounded up.
+    max_radius: i64,
+    /// How many pairs are within `radius`.
+    num_found: usize,
+    band: std::vec::IntoIter<PointPair>,
+}
+
+impl<'a> PairsByDistance<'a> {
+    pub fn new(points: &'a [Point]) -> PairsByDistance<'a> {
+        let (min, max) = points.iter().fold(
+            (
+                Point::new(i64::MAX, i64::MAX, i64::MAX),
+                Point::new(i64::MIN, i64::MIN, i64::MIN),
+            ),
+            |(min, max), p| (min.inf(p), max.sup(p)),
+        );
+        PairsByDistance {
+            points,
+            radius: None,
+            max_radius: if points.is_empty() {
+                0
+            } else {
+                ceil_sqrt(squared_distance(&min, &max))
+            },
+            num_found: 0,
+            band: Vec::new().into_iter(),
+        }
+    }
+
+    /// Picks how far out to search next, aiming for a band of the planned size.
+    fn next_radius(&self) -> i64 {
+        // Only the band sizes are estimated in floating point; which pairs fall in a band is
+        // still decided exactly.
+        let target = self.points.len().max(self.num_found / 4).max(16) as f64;
+        let radius = match self.radius {
+            // Points spread evenly through their bounding box would each have one neighbour
+            // within this distance.
+            None => self.max_radius as f64 / (self.points.len().max(1) as f64).cbrt(),
+            // Pairs within a radius grow with its cube.
+            Some(radius) if self.num_found > 0 => {
+                let growth = ((self.num_found as f64 + target) / self.num_found as f64).cbrt();
+                radius as f64 * growth
+            }
+            Some(radius) => radius as f64 * 2.0,
+        };
+        // Always move out by at least one, so that every band is new.
+        (radius.ceil() as i64).max(self.radius.map_or(1, |radius| radius.saturating_add(1)))
+    }
+}
+
+impl Iterator for PairsByDistance<'_> {
+    type Item = PointPair;
+
+    fn next(&mut self) -> Option<PointPair> {
+        loop {
+            if let Some(pair) = self.band.next() {
+                return Some(pair);
+            }
+            if self.points.len() < 2 || self.radius.is_some_and(|r| r >= self.max_radius) {
+                return None;
+            }
+
+            let above = self.radius;
+            let radius = self.next_radius();
+            let mut band = BucketGrid::new(self.points, radius).pairs_within(above, radius);
+            band.sort_unstable();
+            log::debug!(
+                "Found {} pairs between {:?} and {}",
+                band.len(),
+                above,
+                radius
+            );
+            self.num_found += band.len();
+            self.radius = Some(radius);
+            self.band = band.into_iter();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A deterministic scattering of points, with some repeated.
+    fn scattered_points(n: usize) -> Vec<Point> {
+        let mut state = 12345u64;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 33) % 1000) as i64 - 500
+        };
+        let mut points: Vec<Point> = (0..n).map(|_| Point::new(next(), next(), next())).collect();
+        points.push(points[0]);
+        points
+    }
+
+    fn all_pairs_by_distance(points: &[Point]) -> Vec<PointPair> {
+        let mut pairs = Vec::new();
+        for id_a in 0..points.len() {
+            for id_b in (id_a + 1)..points.len() {
+                pairs.push(PointPair {
+                    squared_distance: squared_distance(&points[id_a], &points[id_b]),
+                    id_a,
+                    id_b,
+                });
+            }
+        }
+        pairs.sort();
+        pairs
+    }
+
+    #[test]
+    fn pairs_within_matches_brute_force() {
+        let points = scattered_points(200);
+        let grid = BucketGrid::new(&points, 100);
+        let mut pairs = grid.pairs_within(Some(50), 100);
+        pairs.sort_by_key(|p| (p.id_a, p.id_b));
+
+        let mut expected: Vec<PointPair> = all_pairs_by_distance(&points)
+            .into_iter()
+            .filter(|p| p.squared_distance > 50 * 50 && p.squared_distance <= 100 * 100)
+            .collect();
+        expected.sort_by_key(|p| (p.id_a, p.id_b));
+        assert_eq!(pairs, expected);
+    }
+
+    #[test]
+    fn pairs_by_distance_yields_every_pair_in_order() {
+        let points = scattered_points(150);
+        let pairs: Vec<PointPair> = PairsByDistance::new(&points).collect();
+        assert_eq!(pairs, all_pairs_by_distance(&points));
+    }
+
+    #[test]
+    fn pairs_by_distance_of_identical_points() {
+        let points = vec![Point::new(1, 2, 3); 4];
+        let pairs: Vec<(usize, usize)> = PairsByDistance::new(&points)
+            .map(|p| (p.id_a, p.id_b))
+            .collect();
+        assert_eq!(pairs, vec![(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn pairs_by_distance_of_too_few_points() {
+        assert_eq!(PairsByDistance::new(&[]).count(), 0);
+        assert_eq!(PairsByDistance::new(&[Point::new(0, 0, 0)]).count(), 0);
+    }
+
+    #[test]
+    fn equidistant_pairs_are_ordered_by_id() {
+        let points = [
+            Point::new(0, 0, 0),
+            Point::new(0, 3, 0),
+            Point::new(-3, 0, 0),
+            Point::new(0, 0, 3),
+            Point::new(2, 2, 1),
+        ];
+        let pairs: Vec<(usize, usize, i128)> = PairsByDistance::new(&points)
+            .take(5)
+            .map(|p| (p.id_a, p.id_b, p.squared_distance))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![(1, 4, 6), (0, 1, 9), (0, 2, 9), (0, 3, 9), (0, 4, 9)]
+        );
+    }
+
+    #[test]
+    fn squared_distance_is_exact() {
+        let a = Point::new(i64::from(i32::MIN), 0, 0);
+        let b = Point::new(i64::from(i32::MAX), 0, 0);
+        assert_eq!(squared_distance(&a, &b), ((1i128 << 32) - 1).pow(2));
+    }
+}
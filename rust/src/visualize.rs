@@ -0,0 +1,386 @@
+// Pictures of how a day's solution works: frames of a grid, or a vector drawing
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::error::AppError;
+use crate::utils::grid::Grid;
+
+/// How a cell is drawn, by what it means rather than by colour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// Background, like empty floor.
+    Empty,
+    /// Something present but not involved in this frame.
+    Solid,
+    /// Something taken away in an earlier frame.
+    Removed,
+    /// Something active, like a travelling beam.
+    Lit,
+    /// What changed in this frame.
+    Highlighted,
+}
+
+impl Style {
+    const ALL: [Style; 5] = [
+        Style::Empty,
+        Style::Solid,
+        Style::Removed,
+        Style::Lit,
+        Style::Highlighted,
+    ];
+
+    fn ansi_code(&self) -> &'static str {
+        match self {
+            Style::Empty => "2",
+            Style::Solid => "0",
+            Style::Removed => "31",
+            Style::Lit => "1;33",
+            Style::Highlighted => "1;36",
+        }
+    }
+
+    fn rgb(&self) -> [u8; 3] {
+        match self {
+            Style::Empty => [20, 20, 30],
+            Style::Solid => [170, 170, 170],
+            Style::Removed => [200, 60, 60],
+            Style::Lit => [240, 200, 60],
+            Style::Highlighted => [80, 200, 220],
+        }
+    }
+
+    fn palette_index(&self) -> u8 {
+        Style::ALL
+            .iter()
+            .position(|style| style == self)
+            .unwrap_or(0) as u8
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub glyph: char,
+    pub style: Style,
+}
+
+impl Cell {
+    pub fn new(glyph: char, style: Style) -> Cell {
+        Cell { glyph, style }
+    }
+}
+
+/// One step of a solution, with a caption saying what happened in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub caption: String,
+    pub cells: Grid<Cell>,
+}
+
+impl Frame {
+    /// The frame as plain text: the caption, then one line per row.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\n", self.caption);
+        for row in self.cells.rows() {
+            text.extend(row.iter().map(|cell| cell.glyph));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// The frame coloured with ANSI escape codes, for printing to a terminal.
+    pub fn to_ansi(&self) -> String {
+        let mut text = format!("\x1b[1m{}\x1b[0m\n", self.caption);
+        for row in self.cells.rows() {
+            let mut style = None;
+            for cell in row {
+                if style != Some(cell.style) {
+                    text.push_str(&format!("\x1b[0;{}m", cell.style.ansi_code()));
+                    style = Some(cell.style);
+                }
+                text.push(cell.glyph);
+            }
+            text.push_str("\x1b[0m\n");
+        }
+        text
+    }
+}
+
+/// What a day draws to show how it solves a part.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Visualization {
+    /// Steps through a grid, which can be played in a terminal.
+    Frames(Vec<Frame>),
+    /// A single SVG document, for inputs too large to draw as text.
+    Svg(String),
+}
+
+/// Choices about what to draw, which days without them ignore.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VisualizeOptions {
+    /// Also draw candidates that were considered and rejected.
+    pub show_rejected: bool,
+}
+
+/// Where to save a visualization, chosen by the path's extension.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Export {
+    /// One numbered text file per frame, in this directory.
+    TextFrames(PathBuf),
+    /// An animated GIF.
+    Gif(PathBuf),
+    /// An SVG file.
+    Svg(PathBuf),
+}
+
+impl Export {
+    pub fn for_path(path: &Path) -> Export {
+        let has_extension = |expected: &str| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case(expected))
+        };
+        if has_extension("gif") {
+            Export::Gif(path.to_path_buf())
+        } else if has_extension("svg") {
+            Export::Svg(path.to_path_buf())
+        } else {
+            Export::TextFrames(path.to_path_buf())
+        }
+    }
+
+    pub fn write(&self, visualization: &Visualization, delay: Duration) -> Result<(), AppError> {
+        match (self, visualization) {
+            (Export::TextFrames(dir), Visualization::Frames(frames)) => {
+                write_text_frames(dir, frames)
+            }
+            (Export::Gif(path), Visualization::Frames(frames)) => write_gif(path, frames, delay),
+            (Export::Svg(path), Visualization::Svg(svg)) => fs::write(path, svg)
+                .map_err(|err| AppError::io(format!("could not write {}", path.display()), err)),
+            (Export::Svg(_), Visualization::Frames(_)) => Err(AppError::Usage(
+                "this day draws frames, so export to a .gif or a directory instead".to_string(),
+            )),
+            (_, Visualization::Svg(_)) => Err(AppError::Usage(
+                "this day draws an SVG, so export to a .svg path instead".to_string(),
+            )),
+        }
+    }
+}
+
+/// Shows a visualization in the terminal, which only works for frames.
+pub fn show(visualization: &Visualization, delay: Duration) -> Result<(), AppError> {
+    match visualization {
+        Visualization::Frames(frames) => play(frames, delay),
+        Visualization::Svg(_) => Err(AppError::Usage(
+            "this day draws an SVG, which a terminal cannot show; pass --export with a .svg path"
+                .to_string(),
+        )),
+    }
+}
+
+/// Plays the frames in a terminal, redrawing over the previous frame each time.
+///
+/// Frames go to stderr, so that stdout only has the answer in whichever `--format` was asked for.
+fn play(frames: &[Frame], delay: Duration) -> Result<(), AppError> {
+    let mut stderr = io::stderr().lock();
+    for (i, frame) in frames.iter().enumerate() {
+        if i > 0 {
+            thread::sleep(delay);
+        }
+        // Clear the screen and move the cursor home.
+        write!(stderr, "\x1b[2J\x1b[H{}", frame.to_ansi())
+            .and_then(|()| stderr.flush())
+            .map_err(|err| AppError::io("could not draw frame", err))?;
+    }
+    Ok(())
+}
+
+fn write_text_frames(dir: &Path, frames: &[Frame]) -> Result<(), AppError> {
+    fs::create_dir_all(dir)
+        .map_err(|err| AppError::io(format!("could not create {}", dir.display()), err))?;
+    let width = frames.len().to_string().len();
+    for (i, frame) in frames.iter().enumerate() {
+        let path = dir.join(format!("frame-{:0width$}.txt", i + 1, width = width));
+        fs::write(&path, frame.to_text())
+            .map_err(|err| AppError::io(format!("could not write {}", path.display()), err))?;
+    }
+    Ok(())
+}
+
+/// Pixels per cell in exported images.
+const GIF_CELL_SIZE: usize = 4;
+
+fn write_gif(path: &Path, frames: &[Frame], delay: Duration) -> Result<(), AppError> {
+    let action = || format!("could not write {}", path.display());
+    let Some(first) = frames.first() else {
+        return Err(AppError::Usage("there are no frames to export".to_string()));
+    };
+    let too_large = || {
+        AppError::Usage(format!(
+            "a {}x{} grid is too large for a GIF",
+            first.cells.num_cols(),
+            first.cells.num_rows()
+        ))
+    };
+    let width = u16::try_from(first.cells.num_cols() * GIF_CELL_SIZE).map_err(|_| too_large())?;
+    let height = u16::try_from(first.cells.num_rows() * GIF_CELL_SIZE).map_err(|_| too_large())?;
+
+    let palette: Vec<u8> = Style::ALL.iter().flat_map(Style::rgb).collect();
+    let file = File::create(path).map_err(|err| AppError::io(action(), err))?;
+    let mut encoder = gif::Encoder::new(file, width, height, &palette)
+        .map_err(|err| AppError::io(action(), err))?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(|err| AppError::io(action(), err))?;
+    // GIF delays are in hundredths of a second.
+    let delay = u16::try_from(delay.as_millis() / 10).unwrap_or(u16::MAX);
+    for frame in frames {
+        let mut gif_frame = gif::Frame::from_indexed_pixels(
+            width,
+            height,
+            frame_pixels(frame, usize::from(width)),
+            None,
+        );
+        gif_frame.delay = delay;
+        encoder
+            .write_frame(&gif_frame)
+            .map_err(|err| AppError::io(action(), err))?;
+    }
+    Ok(())
+}
+
+/// Palette indexes for every pixel of the frame, row by row, `width` pixels per row.
+fn frame_pixels(frame: &Frame, width: usize) -> Vec<u8> {
+    let mut pixels = Vec::with_capacity(width * frame.cells.num_rows() * GIF_CELL_SIZE);
+    for row in frame.cells.rows() {
+        let mut line = Vec::with_capacity(width);
+        for cell in row {
+            line.extend(std::iter::repeat_n(
+                cell.style.palette_index(),
+                GIF_CELL_SIZE,
+            ));
+        }
+        // Frames are normally the same size, but pad or cut any that are not.
+        line.resize(width, Style::Empty.palette_index());
+        for _ in 0..GIF_CELL_SIZE {
+            pixels.extend_from_slice(&line);
+        }
+    }
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn example_frame() -> Frame {
+        let cells = Grid::parse("@.\n.x", |c| {
+            Ok(match c {
+                '@' => Cell::new(c, Style::Solid),
+                'x' => Cell::new(c, Style::Highlighted),
+                _ => Cell::new(c, Style::Empty),
+            })
+        })
+        .unwrap();
+        Frame {
+            caption: "step 1".to_string(),
+            cells,
+        }
+    }
+
+    #[test]
+    fn frame_to_text() {
+        assert_eq!(example_frame().to_text(), "step 1\n@.\n.x\n");
+    }
+
+    #[test]
+    fn frame_to_ansi() {
+        assert_eq!(
+            example_frame().to_ansi(),
+            "\x1b[1mstep 1\x1b[0m\n\
+             \x1b[0;0m@\x1b[0;2m.\x1b[0m\n\
+             \x1b[0;2m.\x1b[0;1;36mx\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn export_for_path() {
+        assert_eq!(
+            Export::for_path(Path::new("out/day4.GIF")),
+            Export::Gif(PathBuf::from("out/day4.GIF"))
+        );
+        assert_eq!(
+            Export::for_path(Path::new("day9.svg")),
+            Export::Svg(PathBuf::from("day9.svg"))
+        );
+        assert_eq!(
+            Export::for_path(Path::new("out/day4")),
+            Export::TextFrames(PathBuf::from("out/day4"))
+        );
+    }
+
+    #[test]
+    fn frame_pixels_scale_cells() {
+        let pixels = frame_pixels(&example_frame(), 2 * GIF_CELL_SIZE);
+        assert_eq!(pixels.len(), 4 * GIF_CELL_SIZE * GIF_CELL_SIZE);
+        assert_eq!(pixels[0], Style::Solid.palette_index());
+        assert_eq!(pixels[GIF_CELL_SIZE], Style::Empty.palette_index());
+        assert_eq!(*pixels.last().unwrap(), Style::Highlighted.palette_index());
+    }
+
+    #[test]
+    fn export_writes_files() {
+        let dir = env::temp_dir().join(format!("aoc-visualize-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let frames = Visualization::Frames(vec![example_frame(), example_frame()]);
+        let delay = Duration::from_millis(100);
+
+        Export::TextFrames(dir.join("frames"))
+            .write(&frames, delay)
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("frames/frame-2.txt")).unwrap(),
+            "step 1\n@.\n.x\n"
+        );
+
+        Export::Gif(dir.join("frames.gif"))
+            .write(&frames, delay)
+            .unwrap();
+        assert!(
+            fs::read(dir.join("frames.gif"))
+                .unwrap()
+                .starts_with(b"GIF89a")
+        );
+
+        let svg = Visualization::Svg("<svg/>".to_string());
+        Export::Svg(dir.join("drawing.svg"))
+            .write(&svg, delay)
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("drawing.svg")).unwrap(),
+            "<svg/>"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn export_rejects_mismatched_format() {
+        let delay = Duration::from_millis(100);
+        let svg = Visualization::Svg("<svg/>".to_string());
+        assert!(matches!(
+            Export::Gif(PathBuf::from("out.gif")).write(&svg, delay),
+            Err(AppError::Usage(_))
+        ));
+        assert!(matches!(
+            Export::Svg(PathBuf::from("out.svg"))
+                .write(&Visualization::Frames(vec![example_frame()]), delay),
+            Err(AppError::Usage(_))
+        ));
+        assert!(matches!(show(&svg, delay), Err(AppError::Usage(_))));
+    }
+}